@@ -0,0 +1,61 @@
+//! Benchmarks for matching the `Origin` of requests against the allowed origins
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rocket::http::Header;
+use rocket::local::blocking::Client;
+use rocket::request::FromRequest;
+use rocket::tokio::runtime::Runtime;
+use rocket_cors::{AllowedOrigins, CorsOptions, Guard};
+
+/// The number of domains whose subdomains are allowed
+const DOMAINS: [usize; 3] = [1, 10, 100];
+
+/// Creates a client with `Cors` in managed state, so that the `Guard` can be used directly
+fn make_client(allowed_origins: AllowedOrigins) -> Client {
+    let cors = CorsOptions {
+        allowed_origins,
+        ..Default::default()
+    }
+    .to_cors()
+    .expect("To not fail");
+
+    Client::tracked(rocket::build().manage(cors)).expect("valid rocket instance")
+}
+
+fn subdomain_origins(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("runtime to be created");
+    let mut group = c.benchmark_group("subdomain_origins");
+
+    for domains in DOMAINS {
+        let regex: Vec<String> = (0..domains)
+            .map(|i| format!(r"^https://(.+)\.acme-{}\.com$", i))
+            .collect();
+        let wildcard: Vec<String> = (0..domains)
+            .map(|i| format!("https://*.acme-{}.com", i))
+            .collect();
+
+        let clients = [
+            ("regex", make_client(AllowedOrigins::some_regex(&regex))),
+            (
+                "wildcard",
+                make_client(AllowedOrigins::some_exact(&wildcard)),
+            ),
+        ];
+
+        for (name, client) in &clients {
+            let origin = format!("https://www.acme-{}.com", domains - 1);
+            let request = client.get("/").header(Header::new("Origin", origin));
+
+            let _ = group.bench_function(BenchmarkId::new(*name, domains), |b| {
+                b.iter(|| {
+                    let outcome = runtime.block_on(Guard::from_request(request.inner()));
+                    assert!(outcome.is_success());
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, subdomain_origins);
+criterion_main!(benches);
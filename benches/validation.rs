@@ -0,0 +1,93 @@
+//! Benchmarks for validating CORS requests and building the CORS response
+//!
+//! The requests are validated with the [`Guard`] directly, so that the numbers are not dominated by
+//! Rocket's routing and dispatching.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rocket::http::{Header, Method};
+use rocket::local::blocking::{Client, LocalRequest};
+use rocket::request::FromRequest;
+use rocket::tokio::runtime::Runtime;
+use rocket_cors::{AllowedHeaders, AllowedOrigins, CorsOptions, Guard};
+
+const ORIGIN: &str = "https://www.acme.com";
+
+/// The allowed origins to benchmark against, all of which allow [`ORIGIN`]
+fn allowed_origins() -> Vec<(&'static str, AllowedOrigins)> {
+    vec![
+        ("exact", AllowedOrigins::some_exact(&[ORIGIN])),
+        (
+            "regex",
+            AllowedOrigins::some_regex(&[r"^https://(.+)\.acme\.com$"]),
+        ),
+        ("all", AllowedOrigins::all()),
+    ]
+}
+
+/// Creates a client with `Cors` in managed state, so that the `Guard` can be used directly
+fn make_client(allowed_origins: AllowedOrigins) -> Client {
+    let cors = CorsOptions {
+        allowed_origins,
+        allowed_methods: vec![Method::Get, Method::Post]
+            .into_iter()
+            .map(From::from)
+            .collect(),
+        allowed_headers: AllowedHeaders::some(&["Authorization", "Accept", "Content-Type"]),
+        allow_credentials: true,
+        expose_headers: ["X-Request-Id"].iter().map(|s| (*s).to_string()).collect(),
+        max_age: Some(3600),
+        ..Default::default()
+    }
+    .to_cors()
+    .expect("To not fail");
+
+    Client::tracked(rocket::build().manage(cors)).expect("valid rocket instance")
+}
+
+fn preflight_request(client: &Client) -> LocalRequest<'_> {
+    client
+        .options("/")
+        .header(Header::new("Origin", ORIGIN))
+        .header(Header::new("Access-Control-Request-Method", "POST"))
+        .header(Header::new(
+            "Access-Control-Request-Headers",
+            "Authorization, Content-Type",
+        ))
+}
+
+fn actual_request(client: &Client) -> LocalRequest<'_> {
+    client.get("/").header(Header::new("Origin", ORIGIN))
+}
+
+fn bench_requests(
+    c: &mut Criterion,
+    group: &str,
+    make_request: for<'c> fn(&'c Client) -> LocalRequest<'c>,
+) {
+    let runtime = Runtime::new().expect("runtime to be created");
+    let mut group = c.benchmark_group(group);
+
+    for (name, allowed_origins) in allowed_origins() {
+        let client = make_client(allowed_origins);
+        let request = make_request(&client);
+
+        let _ = group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| {
+                let outcome = runtime.block_on(Guard::from_request(request.inner()));
+                assert!(outcome.is_success());
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn preflight(c: &mut Criterion) {
+    bench_requests(c, "preflight", preflight_request);
+}
+
+fn actual_request_benches(c: &mut Criterion) {
+    bench_requests(c, "actual_request", actual_request);
+}
+
+criterion_group!(benches, preflight, actual_request_benches);
+criterion_main!(benches);
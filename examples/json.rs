@@ -29,6 +29,7 @@ fn main() {
         send_wildcard: false,
         fairing_route_base: "/mycors".to_string(),
         fairing_route_rank: 0,
+        ..Default::default()
     };
 
     println!("Default settings");
@@ -1,4 +1,5 @@
-use rocket::error::Error;
+use std::error::Error;
+
 use rocket::http::Method;
 use rocket::response::Responder;
 use rocket::{get, options, routes, State};
@@ -54,7 +55,7 @@ fn cors_options() -> CorsOptions {
 }
 
 #[rocket::main]
-async fn main() -> Result<(), Error> {
+async fn main() -> Result<(), Box<dyn Error>> {
     let _ = rocket::build()
         .mount("/", routes![borrowed, owned, owned_options,])
         .mount("/", rocket_cors::catch_all_options_routes()) // mount the catch all routes
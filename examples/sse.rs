@@ -0,0 +1,50 @@
+use std::error::Error;
+use std::time::Duration;
+
+use rocket::http::Method;
+use rocket::response::stream::{Event, EventStream};
+use rocket::tokio::time;
+use rocket::{get, routes};
+use rocket_cors::{AllowedOrigins, Guard, Responder};
+
+/// Stream a count of the seconds since the client connected, as server-sent events
+///
+/// The CORS headers are sent before the first event, so a browser `EventSource` on
+/// `https://www.acme.com` can listen to it:
+///
+/// ```js
+/// const events = new EventSource("http://localhost:8000/events", { withCredentials: true });
+/// events.addEventListener("tick", (event) => console.log(event.data));
+/// ```
+#[get("/events")]
+fn events(cors: Guard<'_>) -> Responder<EventStream![]> {
+    cors.responder(EventStream! {
+        let mut interval = time::interval(Duration::from_secs(1));
+        for seconds in 0.. {
+            let _ = interval.tick().await;
+            yield Event::data(seconds.to_string()).event("tick");
+        }
+    })
+}
+
+#[rocket::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let allowed_origins = AllowedOrigins::some_exact(&["https://www.acme.com"]);
+
+    // `EventSource` only sends GET requests, without custom headers
+    let cors = rocket_cors::CorsOptions {
+        allowed_origins,
+        allowed_methods: vec![Method::Get].into_iter().map(From::from).collect(),
+        allow_credentials: true,
+        ..Default::default()
+    }
+    .to_cors()?;
+
+    let _ = rocket::build()
+        .mount("/", routes![events])
+        .manage(cors)
+        .launch()
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,296 @@
+//! CORS validation of plain requests, independently of Rocket's request and response types
+//!
+//! The guard, the fairings and the manual responders validate Rocket's requests, and add the
+//! headers to Rocket's responses. Use this module to validate a request that you only have the
+//! method and headers of, such as in a proxy, and to get the CORS headers as a list of names and
+//! values.
+//!
+//! Requests and responses of the [`http`](::http) crate, which hyper and tower use, can be
+//! converted with [`request_from_header_map`] and [`to_header_map`].
+//!
+//! Origins in the [origin store](crate::store) are only allowed if the store answers from its
+//! [cache](crate::store::OriginStore::cached), because validation is synchronous.
+//!
+//! # Example
+//! ```rust
+//! use std::collections::HashMap;
+//!
+//! use rocket_cors::core::{self, CorsRequest};
+//! use rocket_cors::CorsOptions;
+//!
+//! let cors = CorsOptions::strict(&["https://www.acme.com"])
+//!     .to_cors()
+//!     .expect("To not fail");
+//!
+//! let headers: HashMap<&str, &str> = [("Origin", "https://www.acme.com")].into_iter().collect();
+//! let request = CorsRequest::new("GET", |name: &str| headers.get(name).copied())
+//!     .expect("a valid method")
+//!     .path("/users");
+//!
+//! let response_headers = core::validate(&cors, &request).expect("to be allowed");
+//! assert!(response_headers.contains(&(
+//!     "Access-Control-Allow-Origin",
+//!     "https://www.acme.com".to_string()
+//! )));
+//! ```
+
+use std::str::FromStr;
+
+use ::http::header::{HeaderMap, HeaderName, HeaderValue};
+use rocket::http;
+use rocket::Request;
+
+use crate::store::OriginStore;
+use crate::{
+    error_response, validate_and_build, Cors, Decision, Error, MethodParseError, StoredOrigins,
+};
+
+/// The parts of a request that CORS validation reads
+pub(crate) trait RequestParts {
+    /// Returns the method of the request
+    fn method(&self) -> http::Method;
+
+    /// Returns the first value of the header `name`, if any
+    fn header(&self, name: &str) -> Option<&str>;
+
+    /// Returns the path of the request
+    fn path(&self) -> &str;
+
+    /// Returns the answer of `store` for `origin`, if it has one
+    fn stored_origin(&self, store: &dyn OriginStore, origin: &str) -> Option<bool>;
+
+    /// Records the [`Decision`] made for the request. Does nothing by default.
+    fn decide(&self, decision: Decision) {
+        let _ = decision;
+    }
+}
+
+/// The answer of the store is looked up before validation, and cached for the request, like the
+/// decision
+impl RequestParts for Request<'_> {
+    fn method(&self) -> http::Method {
+        Request::method(self)
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers().get_one(name)
+    }
+
+    fn path(&self) -> &str {
+        self.uri().path().as_str()
+    }
+
+    fn stored_origin(&self, store: &dyn OriginStore, origin: &str) -> Option<bool> {
+        self.local_cache(StoredOrigins::default)
+            .get(store, origin)
+            .or_else(|| store.cached(origin))
+    }
+
+    fn decide(&self, decision: Decision) {
+        crate::fairing::decide(self, decision);
+    }
+}
+
+/// A request to validate, with its headers read through `header`
+///
+/// `header` is called with the names of the headers in the case used by the specification, such
+/// as `Access-Control-Request-Method`, and returns the first value of the header. Header names are
+/// case-insensitive, so look them up accordingly.
+pub struct CorsRequest<'a, F> {
+    method: http::Method,
+    path: &'a str,
+    header: F,
+}
+
+impl<'a, F> CorsRequest<'a, F>
+where
+    F: Fn(&str) -> Option<&'a str>,
+{
+    /// Create a request with `method`, which must be one of the methods that Rocket supports
+    pub fn new(method: &str, header: F) -> Result<Self, Error> {
+        let method = http::Method::from_str(method)
+            .map_err(|()| Error::BadRequestMethod(MethodParseError(method.to_string())))?;
+        Ok(CorsRequest {
+            method,
+            path: "",
+            header,
+        })
+    }
+
+    /// Sets the path of the request, which is passed to
+    /// [`CorsOptions::on_denied`](crate::CorsOptions::on_denied). Empty by default.
+    #[must_use]
+    pub fn path(mut self, path: &'a str) -> Self {
+        self.path = path;
+        self
+    }
+}
+
+impl<'a, F> RequestParts for CorsRequest<'a, F>
+where
+    F: Fn(&str) -> Option<&'a str>,
+{
+    fn method(&self) -> http::Method {
+        self.method
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        (self.header)(name)
+    }
+
+    fn path(&self) -> &str {
+        self.path
+    }
+
+    fn stored_origin(&self, store: &dyn OriginStore, origin: &str) -> Option<bool> {
+        store.cached(origin)
+    }
+}
+
+/// Validates `request` with `cors`, and returns the headers to add to its response
+///
+/// The headers of a request that is not a CORS request may be empty, or only vary on `Origin`.
+/// The CORS headers that are not returned should be removed from the response. A `Vary` header
+/// should be merged with the one in the response, if any.
+pub fn validate<'a, F>(
+    cors: &Cors,
+    request: &CorsRequest<'a, F>,
+) -> Result<Vec<(&'static str, String)>, Error>
+where
+    F: Fn(&str) -> Option<&'a str>,
+{
+    validate_and_build(cors, request).map(|headers| headers.header_pairs())
+}
+
+/// Returns the headers to add to the error response to `request`, which failed validation
+pub fn error_headers<'a, F>(
+    cors: &Cors,
+    request: &CorsRequest<'a, F>,
+) -> Vec<(&'static str, String)>
+where
+    F: Fn(&str) -> Option<&'a str>,
+{
+    error_response(cors, request).header_pairs()
+}
+
+/// Returns whether `request` is a pre-flight request
+pub fn is_preflight<'a, F>(request: &CorsRequest<'a, F>) -> bool
+where
+    F: Fn(&str) -> Option<&'a str>,
+{
+    crate::is_preflight(request)
+}
+
+/// Create a request to validate from the `method` and `headers` of a request of the
+/// [`http`](::http) crate
+///
+/// Header values that are not visible ASCII are ignored.
+pub fn request_from_header_map<'a>(
+    method: &::http::Method,
+    headers: &'a HeaderMap,
+) -> Result<CorsRequest<'a, impl Fn(&str) -> Option<&'a str> + 'a>, Error> {
+    CorsRequest::new(method.as_str(), move |name: &str| {
+        headers.get(name).and_then(|value| value.to_str().ok())
+    })
+}
+
+/// Convert the headers returned by [`validate`] or [`error_headers`] to a `HeaderMap` of the
+/// [`http`](::http) crate
+///
+/// Headers that are not valid names or values are left out, and logged.
+pub fn to_header_map<I>(headers: I) -> HeaderMap
+where
+    I: IntoIterator<Item = (&'static str, String)>,
+{
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        match (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::try_from(value),
+        ) {
+            (Ok(name), Ok(value)) => {
+                let _ = map.append(name, value);
+            }
+            _ => warn_!(
+                "Leaving out the {} header, which is not a valid header",
+                name
+            ),
+        }
+    }
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{AllowedOrigins, CorsOptions};
+
+    fn cors() -> Cors {
+        CorsOptions::default()
+            .allowed_origins(AllowedOrigins::some_exact(&["https://www.acme.com"]))
+            .max_age(Some(3600))
+            .to_cors()
+            .expect("To not fail")
+    }
+
+    #[test]
+    fn plain_requests_are_validated() {
+        let cors = cors();
+        let headers: HashMap<&str, &str> = [
+            ("Origin", "https://www.acme.com"),
+            ("Access-Control-Request-Method", "GET"),
+        ]
+        .into_iter()
+        .collect();
+        let request =
+            CorsRequest::new("OPTIONS", |name: &str| headers.get(name).copied()).expect("valid");
+        assert!(is_preflight(&request));
+
+        let response = validate(&cors, &request).expect("to not fail");
+        let response: HashMap<&str, String> = response.into_iter().collect();
+        assert_eq!(
+            response["Access-Control-Allow-Origin"],
+            "https://www.acme.com"
+        );
+        assert_eq!(response["Access-Control-Max-Age"], "3600");
+        assert!(response["Vary"].contains("Origin"));
+    }
+
+    #[test]
+    fn plain_requests_are_denied() {
+        let cors = cors();
+        let request = CorsRequest::new("GET", |name: &str| {
+            (name == "Origin").then_some("https://www.evil.com")
+        })
+        .expect("valid");
+        assert!(!is_preflight(&request));
+
+        assert_matches!(validate(&cors, &request), Err(Error::OriginNotAllowed(_)));
+        assert_eq!(
+            error_headers(&cors, &request),
+            vec![("Vary", "Origin".to_string())]
+        );
+
+        assert!(CorsRequest::new("NOT A METHOD", |_: &str| None).is_err());
+    }
+    #[test]
+    fn header_maps_are_converted() {
+        let cors = cors();
+        let mut headers = HeaderMap::new();
+        let _ = headers.insert(
+            ::http::header::ORIGIN,
+            HeaderValue::from_static("https://www.acme.com"),
+        );
+        let request = request_from_header_map(&::http::Method::GET, &headers).expect("valid");
+
+        let response = to_header_map(validate(&cors, &request).expect("to not fail"));
+        assert_eq!(
+            response[::http::header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://www.acme.com"
+        );
+        assert_eq!(response[::http::header::VARY], "Origin");
+        assert!(!response.contains_key(::http::header::ACCESS_CONTROL_MAX_AGE));
+    }
+}
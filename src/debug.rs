@@ -0,0 +1,123 @@
+//! A route serving the effective configuration of [`Cors`], enabled with the `debug-route` feature
+
+use std::sync::Arc;
+
+use rocket::http::{ContentType, Method};
+use rocket::{route, Data, Request, Route};
+
+use crate::Cors;
+
+impl Cors {
+    /// Returns a `GET` route at `path` serving the effective configuration as JSON
+    ///
+    /// The configuration is shown after the options have been parsed, so operators can confirm
+    /// the policy that a running instance has actually loaded. It is serialized like
+    /// [`Cors::to_options`], with the lists sorted. Hooks such as
+    /// [`CorsOptions::metrics`](crate::CorsOptions::metrics) are not shown.
+    ///
+    /// The route is only served if you mount it. It discloses your CORS policy, so you might want
+    /// to mount it behind authentication, or only in development.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rocket_cors::CorsOptions;
+    ///
+    /// let cors = CorsOptions::default().to_cors().expect("To not fail");
+    /// let route = cors.debug_route("/cors/config");
+    ///
+    /// let _ = rocket::build().mount("/", vec![route]).attach(cors);
+    /// ```
+    pub fn debug_route(&self, path: &str) -> Route {
+        let json =
+            serde_json::to_string_pretty(self).expect("serializing the configuration not to fail");
+        Route::new(Method::Get, path, DebugRoute(json.into()))
+    }
+}
+
+/// Handler serving the JSON configuration of [`Cors`]
+#[derive(Clone)]
+struct DebugRoute(Arc<str>);
+
+#[rocket::async_trait]
+impl route::Handler for DebugRoute {
+    async fn handle<'r>(&self, request: &'r Request<'_>, _: Data<'r>) -> route::Outcome<'r> {
+        route::Outcome::from(request, (ContentType::JSON, self.0.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+
+    use crate::{AllowedHeaders, AllowedOrigins, CorsOptions};
+
+    #[test]
+    fn route_serves_the_effective_configuration() {
+        let cors = CorsOptions::default()
+            .allowed_origins(AllowedOrigins::some(
+                &["https://www.acme.com", "https://*.acme.org"],
+                &["^https://(.+).acme.net$"],
+            ))
+            .allowed_methods(["Post", "Get"].iter().map(|s| s.parse().unwrap()).collect())
+            .allowed_headers(AllowedHeaders::some(&["X-Custom", "Authorization"]))
+            .expose_headers(["X-Exposed"].iter().map(|s| (*s).to_string()).collect())
+            .max_age(Some(42))
+            .to_cors()
+            .expect("To not fail");
+
+        let route = cors.debug_route("/config");
+        let client = Client::tracked(rocket::build().mount("/", vec![route])).unwrap();
+        let response = client.get("/config").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.content_type(),
+            Some(rocket::http::ContentType::JSON)
+        );
+
+        let actual: serde_json::Value =
+            serde_json::from_str(&response.into_string().unwrap()).expect("to be valid JSON");
+        let expected = serde_json::json!({
+            "allowed_origins": {
+                "Some": {
+                    "allow_null": false,
+                    "exact": ["https://*.acme.org", "https://www.acme.com"],
+                    "regex": ["^https://(.+).acme.net$"]
+                }
+            },
+            "allowed_methods": ["GET", "POST"],
+            "allowed_headers": { "Some": ["Authorization", "X-Custom"] },
+            "allow_credentials": false,
+            "expose_headers": ["X-Exposed"],
+            "max_age": 42,
+            "send_wildcard": false,
+            "echo_credentialed_origins": false,
+            "fairing_route_base": "/cors",
+            "fairing_route_rank": 0,
+            "fairing_exempt_routes": [],
+            "fairing_scope": null,
+            "unmatched_options": "NoContent",
+            "headers_on_error": false,
+            "vary_merge": "Adjoin",
+            "overwrite": true,
+            "always_vary_origin": false,
+            "cross_origin_opener_policy": null,
+            "cross_origin_embedder_policy": null,
+            "development_only": false,
+            "strict_validation": false,
+            "strict_request_headers": false,
+            "report_only": false,
+            "minimize_preflight": false,
+            "include_options_method": null,
+            "enforce_on_actual_requests": false,
+            "on_invalid_origin": "Reject",
+            "regex_size_limit": null,
+            "regex_dfa_size_limit": null,
+            "regex_anchoring": "Warn",
+            "regex_backend": "RegexSet",
+            "origin_cache_size": null,
+            "log_level": null
+        });
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,299 @@
+//! A log of denied CORS requests that does not flood your logs
+//!
+//! A misconfigured client can send many requests from an origin that is not allowed. A
+//! [`DenialLog`] logs the first denial of each origin, and then at most one denial per origin for
+//! every [`DenialLog::interval`], with the number of denials that were not logged in between.
+//!
+//! The most recent denial of each origin is kept, so that it can be inspected with
+//! [`DenialLog::recent`], for example from a debug endpoint.
+//!
+//! # Example
+//! ```rust
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! use rocket_cors::denials::DenialLog;
+//! use rocket_cors::CorsOptions;
+//!
+//! let log = Arc::new(DenialLog::new().interval(Duration::from_secs(300)));
+//! let cors = CorsOptions::default()
+//!     .on_denied(log.callback())
+//!     .to_cors()
+//!     .expect("To not fail");
+//!
+//! for denial in log.recent() {
+//!     println!("{:?} denied {} times", denial.origin, denial.count);
+//! }
+//! ```
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use rocket::http::Method;
+
+use crate::{DeniedEvent, Error};
+
+/// The most recent denied CORS request from an origin
+#[derive(Clone, Debug)]
+pub struct Denial {
+    /// The `Origin` header of the request, if any
+    pub origin: Option<String>,
+    /// The method of the request
+    pub method: Method,
+    /// The path of the request
+    pub path: String,
+    /// The reason the request was denied
+    pub error: Error,
+    /// How many requests from the origin have been denied since it was first recorded
+    pub count: u64,
+    /// When the request was denied
+    pub denied_at: SystemTime,
+}
+
+/// A log of denied CORS requests, deduplicated and rate limited per origin
+///
+/// Pass [`DenialLog::callback`] to [`CorsOptions::on_denied`](crate::CorsOptions::on_denied) to
+/// record the denials of a `Cors`. The denials of each origin are logged at most once every
+/// [`DenialLog::interval`].
+///
+/// At most [`DenialLog::capacity`] origins are kept. When a new origin is denied and the log is
+/// full, the origin that was denied the longest time ago is forgotten.
+#[derive(Debug)]
+pub struct DenialLog {
+    interval: Duration,
+    capacity: usize,
+    entries: Mutex<HashMap<Option<String>, Entry>>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    denial: Denial,
+    last_denied: Instant,
+    logged_at: Instant,
+    /// Denials since the denial that was last logged
+    suppressed: u64,
+}
+
+impl DenialLog {
+    /// How often the denials of an origin are logged, unless [`DenialLog::interval`] is used
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+    /// How many origins are kept, unless [`DenialLog::capacity`] is used
+    pub const DEFAULT_CAPACITY: usize = 100;
+
+    /// Create an empty log
+    pub fn new() -> Self {
+        DenialLog {
+            interval: Self::DEFAULT_INTERVAL,
+            capacity: Self::DEFAULT_CAPACITY,
+            entries: Mutex::default(),
+        }
+    }
+
+    /// Sets how often the denials of an origin are logged at most
+    #[must_use]
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets how many origins are kept at most
+    #[must_use]
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Returns a callback recording the denials in this log, for
+    /// [`CorsOptions::on_denied`](crate::CorsOptions::on_denied)
+    pub fn callback(self: &Arc<Self>) -> Arc<dyn Fn(DeniedEvent) + Send + Sync> {
+        let log = Arc::clone(self);
+        Arc::new(move |event| log.record(event))
+    }
+
+    /// Record a denied request, and log it unless the origin has been logged within the
+    /// interval
+    pub fn record(&self, event: DeniedEvent) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().expect("lock not to be poisoned");
+
+        if entries.len() >= self.capacity && !entries.contains_key(&event.origin) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_denied)
+                .map(|(origin, _)| origin.clone());
+            if let Some(oldest) = oldest {
+                let _ = entries.remove(&oldest);
+            }
+        }
+
+        let count = entries
+            .get(&event.origin)
+            .map(|entry| entry.denial.count)
+            .unwrap_or(0)
+            + 1;
+        let denial = Denial {
+            origin: event.origin.clone(),
+            method: event.method,
+            path: event.path,
+            error: event.error,
+            count,
+            denied_at: SystemTime::now(),
+        };
+
+        match entries.get_mut(&event.origin) {
+            Some(entry) if now.duration_since(entry.logged_at) < self.interval => {
+                entry.denial = denial;
+                entry.last_denied = now;
+                entry.suppressed += 1;
+            }
+            entry => {
+                let suppressed = entry.map(|entry| entry.suppressed).unwrap_or(0);
+                log_denial(&denial, suppressed);
+                let _ = entries.insert(
+                    event.origin,
+                    Entry {
+                        denial,
+                        last_denied: now,
+                        logged_at: now,
+                        suppressed: 0,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Returns the most recent denial of each origin, the most recent first
+    pub fn recent(&self) -> Vec<Denial> {
+        let entries = self.entries.lock().expect("lock not to be poisoned");
+        let mut recent: Vec<_> = entries.values().collect();
+        recent.sort_by_key(|entry| Reverse(entry.last_denied));
+        recent
+            .into_iter()
+            .map(|entry| entry.denial.clone())
+            .collect()
+    }
+
+    /// Forget all the denials
+    pub fn clear(&self) {
+        self.entries
+            .lock()
+            .expect("lock not to be poisoned")
+            .clear();
+    }
+}
+
+impl Default for DenialLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn log_denial(denial: &Denial, suppressed: u64) {
+    let origin = denial.origin.as_deref().unwrap_or("no origin");
+    if suppressed == 0 {
+        warn_!(
+            "CORS: Denied {} {} from {}: {}",
+            denial.method,
+            denial.path,
+            origin,
+            denial.error
+        );
+    } else {
+        warn_!(
+            "CORS: Denied {} {} from {}: {} ({} more denials since last logged)",
+            denial.method,
+            denial.path,
+            origin,
+            denial.error,
+            suppressed
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(origin: &str, path: &str) -> DeniedEvent {
+        DeniedEvent {
+            origin: Some(origin.to_string()),
+            method: Method::Get,
+            path: path.to_string(),
+            error: Error::OriginNotAllowed(origin.to_string()),
+        }
+    }
+
+    #[test]
+    fn denials_are_deduplicated_per_origin() {
+        let log = DenialLog::new();
+        log.record(event("https://www.example.com", "/"));
+        log.record(event("https://www.acme.com", "/"));
+        log.record(event("https://www.example.com", "/other"));
+
+        let recent = log.recent();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].origin.as_deref(), Some("https://www.example.com"));
+        assert_eq!(recent[0].path, "/other");
+        assert_eq!(recent[0].count, 2);
+        assert_eq!(recent[1].origin.as_deref(), Some("https://www.acme.com"));
+        assert_eq!(recent[1].count, 1);
+
+        let entries = log.entries.lock().unwrap();
+        assert_eq!(
+            entries[&Some("https://www.example.com".to_string())].suppressed,
+            1
+        );
+    }
+
+    #[test]
+    fn denials_are_logged_again_after_the_interval() {
+        let log = DenialLog::new().interval(Duration::ZERO);
+        log.record(event("https://www.example.com", "/"));
+        log.record(event("https://www.example.com", "/"));
+
+        let entries = log.entries.lock().unwrap();
+        let entry = &entries[&Some("https://www.example.com".to_string())];
+        assert_eq!(entry.suppressed, 0);
+        assert_eq!(entry.denial.count, 2);
+    }
+
+    #[test]
+    fn oldest_origins_are_forgotten_when_full() {
+        let log = DenialLog::new().capacity(2);
+        log.record(event("https://a.example.com", "/"));
+        log.record(event("https://b.example.com", "/"));
+        log.record(event("https://a.example.com", "/"));
+        log.record(event("https://c.example.com", "/"));
+
+        let origins: Vec<_> = log.recent().into_iter().filter_map(|d| d.origin).collect();
+        assert_eq!(origins, ["https://c.example.com", "https://a.example.com"]);
+
+        log.clear();
+        assert!(log.recent().is_empty());
+    }
+
+    #[test]
+    fn denials_are_recorded_from_cors() {
+        use rocket::http::Header;
+        use rocket::local::blocking::Client;
+
+        let log = Arc::new(DenialLog::new());
+        let cors = crate::CorsOptions::default()
+            .allowed_origins(crate::AllowedOrigins::some_exact(&["https://www.acme.com"]))
+            .on_denied(log.callback())
+            .to_cors()
+            .expect("To not fail");
+        let client = Client::tracked(rocket::build().attach(cors)).unwrap();
+
+        let _ = client
+            .get("/")
+            .header(Header::new("Origin", "https://www.example.com"))
+            .dispatch();
+
+        let recent = log.recent();
+        assert_eq!(recent.len(), 1);
+        assert_matches!(&recent[0].error, Error::OriginNotAllowed(_));
+    }
+}
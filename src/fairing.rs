@@ -1,101 +1,580 @@
 //! Fairing implementation
 
-#[allow(unused_imports)]
-use ::log::{error, info};
-use rocket::http::{self, uri::Origin, Status};
-use rocket::{self, error_, info_, outcome::Outcome, Request};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 
+use rocket::catcher::Catcher;
+use rocket::fairing::AdHoc;
+use rocket::figment::Figment;
+use rocket::http::{self, Status};
+use rocket::{self, Request, Route};
+
+use crate::logging;
+use crate::policy::CorsPolicy;
 use crate::{
-    actual_request_response, origin, preflight_response, request_headers, validate, Cors, Error,
+    build, error_response, is_preflight, lookup_origin_store, requested, validate, AllOrSome, Cors,
+    CorsHeaders, CorsOptions, EmbedderPolicy, Error, Method, OpenerPolicy, UnmatchedOptions,
+    ValidationResult, VaryMerge,
 };
 
 /// Request Local State to store CORS validation results
+///
+/// A successful validation keeps the CORS response built from the request headers so that they do
+/// not have to be parsed again in `on_response`.
 enum CorsValidation {
-    Success,
-    Failure,
+    /// Not a CORS request, or one that the fairing skips
+    None,
+    /// A successful validation, with what to respond to a pre-flight request that no route matches
+    Success(CorsHeaders, UnmatchedOptions),
+    /// A failed validation, with the status of the error response and the CORS response to add to
+    /// it
+    Failure(Status, CorsHeaders),
 }
 
-/// Create a `Handler` for Fairing error handling
-#[derive(Clone)]
-struct FairingErrorRoute {}
+/// Request Local State recording whether a fairing has added the headers to the response, so that
+/// they are added once when more than one fairing is attached
+#[derive(Default)]
+struct ResponseDecorated(AtomicBool);
 
-#[rocket::async_trait]
-impl rocket::route::Handler for FairingErrorRoute {
-    async fn handle<'r>(
-        &self,
-        request: &'r Request<'_>,
-        _: rocket::Data<'r>,
-    ) -> rocket::route::Outcome<'r> {
-        let status = request
-            .param::<u16>(0)
-            .unwrap_or(Ok(0))
-            .unwrap_or_else(|e| {
-                error_!("Fairing Error Handling Route error: {:?}", e);
-                500
-            });
-        let status = Status::from_code(status).unwrap_or(Status::InternalServerError);
-        Outcome::Error(status)
+/// Request Local State recording whether a fairing has validated the request, so that it is
+/// validated once when more than one fairing is attached
+#[derive(Default)]
+struct RequestValidated(AtomicBool);
+
+/// Returns whether a fairing has already validated `request`, and marks it as validated
+fn already_validated(request: &Request<'_>) -> bool {
+    request
+        .local_cache(RequestValidated::default)
+        .0
+        .swap(true, Ordering::Relaxed)
+}
+
+/// Request Local State to store the cross-origin isolation headers to add to the response
+#[derive(Default)]
+struct IsolationHeaders {
+    opener_policy: Option<OpenerPolicy>,
+    embedder_policy: Option<EmbedderPolicy>,
+    /// Whether headers that are already set are replaced, which they never are with
+    /// `VaryMerge::Reconcile`
+    overwrite: bool,
+}
+
+impl IsolationHeaders {
+    fn new(options: &Cors) -> Self {
+        IsolationHeaders {
+            opener_policy: options.cross_origin_opener_policy,
+            embedder_policy: options.cross_origin_embedder_policy,
+            overwrite: options.overwrite && options.vary_merge != VaryMerge::Reconcile,
+        }
+    }
+
+    /// Add the headers to `response`. Headers that are already set are left alone unless
+    /// `overwrite` is enabled.
+    fn merge(&self, response: &mut rocket::Response<'_>) {
+        let headers = [
+            (
+                "Cross-Origin-Opener-Policy",
+                self.opener_policy.map(|policy| policy.as_str()),
+            ),
+            (
+                "Cross-Origin-Embedder-Policy",
+                self.embedder_policy.map(|policy| policy.as_str()),
+            ),
+        ];
+        for (name, value) in headers {
+            if let Some(value) = value {
+                if self.overwrite || !response.headers().contains(name) {
+                    let _ = response.set_raw_header(name, value);
+                }
+            }
+        }
     }
 }
 
-/// Create a new `Route` for Fairing handling
-fn fairing_route(rank: isize) -> rocket::Route {
-    rocket::Route::ranked(rank, http::Method::Get, "/<status>", FairingErrorRoute {})
+/// The outcome of validating a request, returned by [`decision`] and [`Guard::decision`]
+///
+/// [`Guard::decision`]: crate::Guard::decision
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum Decision {
+    /// The request passed CORS validation
+    #[non_exhaustive]
+    Allowed {
+        /// The `Origin` of the request, which matched the allowed origins
+        origin: String,
+        /// The rule of the allowed origins that the origin matched, or `None` if the request was
+        /// allowed by a [`CorsPolicy`] that is not a [`Cors`]
+        rule: Option<OriginMatch>,
+        /// The method of an actual request, or the `Access-Control-Request-Method` of a
+        /// pre-flight request
+        method: Method,
+        /// The names in the `Access-Control-Request-Headers` of a pre-flight request, sorted.
+        /// Empty for actual requests.
+        headers: Vec<String>,
+    },
+    /// The request failed CORS validation, but was let through because of
+    /// [`CorsOptions::report_only`]
+    ReportOnly,
+    /// The request failed CORS validation
+    Denied(Error),
 }
 
-/// Modifies a `Request` to route to Fairing error handler
-fn route_to_fairing_error_handler(options: &Cors, status: u16, request: &mut Request<'_>) {
-    let origin = Origin::parse_owned(format!("{}/{}", options.fairing_route_base, status)).unwrap();
+/// The rule of the [`AllowedOrigins`](crate::AllowedOrigins) that allowed the origin of a request
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum OriginMatch {
+    /// All origins are allowed
+    All,
+    /// The origin is `null`, and `allow_null` is set
+    Null,
+    /// The origin is one of the exact origins
+    Exact,
+    /// The origin is a subdomain of one of the wildcard origins, such as `https://*.acme.com`
+    Subdomain,
+    /// The origin matched one of the regexes
+    Regex,
+    /// The origin is in the [origin store](crate::store)
+    Store,
+}
+
+/// Request Local State to store the [`Decision`] for a request, which is read by [`decision`]
+///
+/// Unlike [`CorsValidation`], this can be read before the fairing has run, so the decision is set
+/// in place.
+#[derive(Default)]
+struct DecisionCell(Mutex<Option<Decision>>);
 
-    request.set_method(http::Method::Get);
-    request.set_uri(origin);
+/// Returns the CORS [`Decision`] for `request`
+///
+/// Downstream fairings, request guards and telemetry can use this to observe whether the request
+/// passed CORS validation, and why. The decision is made by the fairing, or by the
+/// [`Guard`](crate::Guard) or a [`ManualResponder`](crate::ManualResponder) in routes that use
+/// them. Returns `None` if the request has not been validated, because it is not a CORS request,
+/// because the fairing skipped it, or because the fairing is not attached or has not run yet.
+///
+/// # Example
+/// ```rust
+/// use rocket::request::{FromRequest, Outcome, Request};
+/// use rocket_cors::Decision;
+///
+/// /// The origin of a request that passed CORS validation
+/// struct CorsOrigin(String);
+///
+/// #[rocket::async_trait]
+/// impl<'r> FromRequest<'r> for CorsOrigin {
+///     type Error = ();
+///
+///     async fn from_request(request: &'r Request<'_>) -> Outcome<Self, ()> {
+///         match rocket_cors::decision(request) {
+///             Some(Decision::Allowed { origin, .. }) => Outcome::Success(CorsOrigin(origin)),
+///             _ => Outcome::Forward(rocket::http::Status::Forbidden),
+///         }
+///     }
+/// }
+///
+/// #[rocket::get("/")]
+/// fn origin(origin: CorsOrigin) -> String {
+///     origin.0
+/// }
+///
+/// # fn main() {
+/// let cors = rocket_cors::CorsOptions::default()
+///     .to_cors()
+///     .expect("To not fail");
+/// let _ = rocket::build()
+///     .mount("/", rocket::routes![origin])
+///     .attach(cors);
+/// # }
+/// ```
+pub fn decision(request: &Request<'_>) -> Option<Decision> {
+    request
+        .local_cache(DecisionCell::default)
+        .0
+        .lock()
+        .expect("lock not to be poisoned")
+        .clone()
 }
 
-fn on_response_wrapper(
-    options: &Cors,
-    request: &Request<'_>,
-    response: &mut rocket::Response<'_>,
-) -> Result<(), Error> {
-    let origin = match origin(request)? {
-        None => {
-            // Not a CORS request
-            return Ok(());
-        }
-        Some(origin) => origin,
+/// Records the [`Decision`] for `request`
+pub(crate) fn decide(request: &Request<'_>, decision: Decision) {
+    *request
+        .local_cache(DecisionCell::default)
+        .0
+        .lock()
+        .expect("lock not to be poisoned") = Some(decision);
+}
+
+/// Managed state holding every `Cors` fairing that has been ignited, to detect a fairing being
+/// attached more than once
+///
+/// Fairings are identified by their [`Cors::id`], so a fairing is a duplicate of another if it is
+/// a clone of it. Fairings created separately from equal options are not duplicates.
+#[derive(Default)]
+struct AttachedFairings(Mutex<HashSet<u64>>);
+
+/// Responds to a request that failed validation with the catcher that Rocket would use for
+/// `status`, or an empty response if there is none
+async fn catch<'r>(status: Status, request: &'r Request<'_>) -> rocket::Response<'r> {
+    let matches = |catcher: &&Catcher| {
+        catcher.code.map_or(true, |code| code == status.code)
+            && catcher
+                .base
+                .path()
+                .segments()
+                .prefix_of(request.uri().path().segments())
     };
+    // The catcher for `status` with the longest base, or the default catcher if its base is longer
+    let catcher = request
+        .rocket()
+        .catchers()
+        .filter(matches)
+        .max_by_key(|catcher| (catcher.base.path().segments().len(), catcher.code.is_some()));
+
+    let response = match catcher {
+        Some(catcher) => catcher.handler.handle(status, request).await,
+        None => Err(status),
+    };
+    response.unwrap_or_else(|_| rocket::Response::build().status(status).finalize())
+}
+
+/// Returns whether `route` matches the method and path of `request`, regardless of its query and
+/// format
+fn route_matches(route: &Route, request: &Request<'_>) -> bool {
+    route_matches_method(route, request.method(), request)
+}
+
+/// Returns whether `route` matches `method` and the path of `request`, regardless of its query
+/// and format
+pub(crate) fn route_matches_method(
+    route: &Route,
+    method: http::Method,
+    request: &Request<'_>,
+) -> bool {
+    if route.method != method
+        && !(method == http::Method::Head && route.method == http::Method::Get)
+    {
+        return false;
+    }
 
-    let result = request.local_cache(|| unreachable!("This should not be executed so late"));
+    route_path_matches(route, request)
+}
 
-    if let CorsValidation::Failure = *result {
-        // Nothing else for us to do
-        return Ok(());
+/// Returns whether `route` matches the path of `request`, regardless of its method, query and
+/// format
+fn route_path_matches(route: &Route, request: &Request<'_>) -> bool {
+    let mut request_segments = request.uri().path().segments();
+    for segment in route.uri.path().split('/').filter(|s| !s.is_empty()) {
+        if segment.starts_with('<') && segment.ends_with("..>") {
+            return true;
+        }
+        match request_segments.next() {
+            Some(request_segment) if segment.starts_with('<') || segment == request_segment => {}
+            _ => return false,
+        }
     }
+    request_segments.next().is_none()
+}
 
-    let origin = origin.to_string();
-    let cors_response = if request.method() == http::Method::Options {
-        let headers = request_headers(request)?;
-        preflight_response(options, &origin, headers.as_ref())
-    } else {
-        actual_request_response(options, &origin)
+/// Returns whether a route in `fairing_exempt_routes` matches `request`
+fn is_exempt(options: &Cors, request: &Request<'_>) -> bool {
+    !options.fairing_exempt_routes.is_empty()
+        && request.rocket().routes().any(|route| {
+            route.name.as_ref().map_or(false, |name| {
+                options.fairing_exempt_routes.contains(&**name)
+            }) && route_matches(route, request)
+        })
+}
+
+/// Returns whether the path of `request` begins with the `fairing_scope`, if any
+fn is_in_scope(options: &Cors, request: &Request<'_>) -> bool {
+    let scope = match options.fairing_scope {
+        Some(ref scope) => scope,
+        None => return true,
     };
 
-    cors_response.merge(response);
-
-    // If this was an OPTIONS request and no route can be found, we should turn this
-    // into a HTTP 204 with no content body.
-    // This allows the user to not have to specify an OPTIONS route for everything.
-    //
-    // TODO: Is there anyway we can make this smarter? Only modify status codes for
-    // requests where an actual route exist?
-    if request.method() == http::Method::Options && request.route().is_none() {
-        info_!(
-            "CORS Fairing: Turned missing route {} into an OPTIONS pre-flight request",
+    let mut request_segments = request.uri().path().segments();
+    scope
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .all(|segment| request_segments.next() == Some(segment))
+}
+
+/// Returns whether the fairing of `options` validates `request`, and marks it as validated if so
+fn should_validate(options: &Cors, request: &Request<'_>) -> bool {
+    let _level = logging::level(options.log_level);
+
+    // The request is left to the fairings of other scopes, if any
+    if !is_in_scope(options, request) {
+        debug_!(
+            "CORS Fairing: Skipping {} outside of the fairing scope",
+            request
+        );
+        return false;
+    }
+    if already_validated(request) {
+        debug_!("CORS Fairing: {} has already been validated", request);
+        return false;
+    }
+    if is_exempt(options, request) {
+        debug_!(
+            "CORS Fairing: Skipping {} handled by an exempt route",
             request
         );
-        response.set_status(Status::NoContent);
-        let _ = response.body_mut().take();
+        let _ = request.local_cache(|| CorsValidation::None);
+        return false;
+    }
+    true
+}
+
+/// Validates a request with `options`, unless another fairing has validated it
+pub(crate) async fn on_request_wrapper(options: &Cors, request: &Request<'_>) {
+    let _ = request.local_cache(|| IsolationHeaders::new(options));
+    if !should_validate(options, request) {
+        return;
+    }
+
+    lookup_origin_store(options, request).await;
+    let _level = logging::level(options.log_level);
+    let result = match validate(options, request) {
+        Ok(ValidationResult::None) if !options.always_vary_origin => CorsValidation::None,
+        Ok(ValidationResult::None) => CorsValidation::Success(
+            build(options, ValidationResult::None),
+            options.unmatched_options,
+        ),
+        Ok(result) => CorsValidation::Success(build(options, result), options.unmatched_options),
+        Err(err) => {
+            let cors_response = error_response(options, request);
+            deny(request, err, cors_response)
+        }
+    };
+
+    let _ = request.local_cache(|| result);
+}
+
+/// Validates a request with `policy`, unless another fairing has validated it
+///
+/// Unlike [`on_request_wrapper`], this only knows what the [`CorsPolicy`] tells it, so there is no
+/// fairing scope, exempt routes or cross-origin isolation headers.
+pub(crate) async fn on_policy_request(policy: &dyn CorsPolicy, request: &Request<'_>) {
+    if already_validated(request) {
+        debug_!("CORS Fairing: {} has already been validated", request);
+        return;
+    }
+
+    policy.prepare(request).await;
+    let result = match policy.validate(request) {
+        Ok(cors_response) => {
+            // A `Cors` records the rule that allowed the request when it validates it
+            let origin = request.headers().get_one("Origin");
+            let decided = matches!(decision(request), Some(Decision::Allowed { .. }));
+            if let (Some(origin), Some(_), false) =
+                (origin, cors_response.allowed_origin(), decided)
+            {
+                let (method, headers) = requested(request);
+                decide(
+                    request,
+                    Decision::Allowed {
+                        origin: origin.to_string(),
+                        rule: None,
+                        method,
+                        headers,
+                    },
+                );
+            }
+            CorsValidation::Success(cors_response, policy.unmatched_options())
+        }
+        Err(err) => {
+            let cors_response = policy.error_headers(request);
+            deny(request, err, cors_response)
+        }
+    };
+
+    let _ = request.local_cache(|| result);
+}
+
+/// Records that `request` was denied for `err`, so that its response is replaced in `on_response`
+fn deny(request: &Request<'_>, err: Error, cors_response: CorsHeaders) -> CorsValidation {
+    error_!(
+        origin = request.headers().get_one("Origin"),
+        method = request.method().as_str();
+        "CORS Error: {}", err
+    );
+    decide(request, Decision::Denied(err.clone()));
+    CorsValidation::Failure(err.status(), cors_response)
+}
+
+/// Adds the CORS headers and the cross-origin isolation headers to the response
+pub(crate) async fn on_response_wrapper<'r>(
+    request: &'r Request<'_>,
+    response: &mut rocket::Response<'r>,
+) {
+    let decorated = request.local_cache(ResponseDecorated::default);
+    if decorated.0.swap(true, Ordering::Relaxed) {
+        return;
+    }
+
+    merge_cors_response(request, response).await;
+    request
+        .local_cache(IsolationHeaders::default)
+        .merge(response);
+}
+
+/// Merges the CORS response built in `on_request`, or the response to a failed validation
+async fn merge_cors_response<'r>(request: &'r Request<'_>, response: &mut rocket::Response<'r>) {
+    // Requests outside of the scope of every fairing have not been validated
+    let result = request.local_cache(|| CorsValidation::None);
+
+    let (cors_response, unmatched_options) = match result {
+        CorsValidation::Failure(status, cors_response) => {
+            // The response of the route is replaced by the one of the catcher for the error
+            *response = catch(*status, request).await;
+            cors_response.merge_borrowed(response);
+            // Nothing else for us to do
+            return;
+        }
+        CorsValidation::None => {
+            // Not a CORS request, or a skipped one
+            return;
+        }
+        CorsValidation::Success(cors_response, unmatched_options) => {
+            (cors_response, *unmatched_options)
+        }
+    };
+
+    cors_response.merge_borrowed(response);
+
+    if is_preflight(request) && request.route().is_none() {
+        respond_to_unmatched_preflight(unmatched_options, request, response);
+    }
+}
+
+/// Responds to a pre-flight request that no route matches according to `unmatched_options`
+///
+/// If a route exists for the path with another method, we turn this into a HTTP 204 with no
+/// content body by default. This allows the user to not have to specify an OPTIONS route for
+/// everything, while paths that do not exist at all stay 404.
+fn respond_to_unmatched_preflight(
+    unmatched_options: UnmatchedOptions,
+    request: &Request<'_>,
+    response: &mut rocket::Response<'_>,
+) {
+    let mut methods: Vec<http::Method> = request
+        .rocket()
+        .routes()
+        .filter(|route| route_path_matches(route, request))
+        .map(|route| route.method)
+        .collect();
+    if methods.is_empty() {
+        return;
+    }
+
+    match unmatched_options {
+        UnmatchedOptions::NoContent => {
+            info_!(
+                "CORS Fairing: Turned missing route {} into an OPTIONS pre-flight request",
+                request
+            );
+            response.set_status(Status::NoContent);
+        }
+        UnmatchedOptions::NotFound => return,
+        UnmatchedOptions::MethodNotAllowed => {
+            methods.sort_by_key(|method| method.as_str());
+            methods.dedup();
+            let allow = methods
+                .iter()
+                .map(|method| method.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            response.set_status(Status::MethodNotAllowed);
+            let _ = response.set_raw_header("Allow", allow);
+        }
+    }
+    let _ = response.body_mut().take();
+}
+
+/// Returns a concise summary of the effective policy of `cors`, as lines of names and values
+fn policy_summary(cors: &Cors) -> Vec<(&'static str, String)> {
+    let origins = match cors.allowed_origins {
+        AllOrSome::All => "all".to_string(),
+        AllOrSome::Some(ref origins) => {
+            let mut summary = vec![format!("{} exact", origins.exact.len())];
+            let subdomains = origins.subdomains.wildcard_origins().count();
+            if subdomains > 0 {
+                summary.push(format!("{} subdomain", subdomains));
+            }
+            if let Some(ref regex) = origins.regex {
+                summary.push(format!("regex {}", regex.patterns().join(" ")));
+            }
+            if origins.allow_null {
+                summary.push("null".to_string());
+            }
+            if origins.store.get().is_some() {
+                summary.push("origin store".to_string());
+            }
+            summary.join(", ")
+        }
+    };
+    let methods = cors
+        .allowed_methods
+        .iter()
+        .map(|method| method.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let headers = match cors.allowed_headers {
+        AllOrSome::All => "all".to_string(),
+        AllOrSome::Some(ref headers) => {
+            let mut headers: Vec<_> = headers.iter().map(|header| header.as_str()).collect();
+            headers.sort_unstable();
+            headers.join(", ")
+        }
+    };
+    let max_age = cors
+        .max_age
+        .map_or_else(|| "none".to_string(), |max_age| format!("{}s", max_age));
+
+    vec![
+        ("origins", origins),
+        ("methods", methods),
+        ("headers", headers),
+        ("credentials", cors.allow_credentials.to_string()),
+        ("max age", max_age),
+    ]
+}
+
+impl Cors {
+    /// Returns a fairing that creates the `Cors` fairing when Rocket is ignited, with options
+    /// returned by `options` from Rocket's configuration
+    ///
+    /// This is useful when the options depend on values that are only known at launch. If
+    /// `options` returns an error, or the options are not valid, the error is logged and ignition
+    /// fails.
+    ///
+    /// # Example
+    /// ```rust
+    /// use rocket_cors::{AllowedOrigins, Cors, CorsOptions};
+    ///
+    /// let rocket = rocket::build().attach(Cors::fairing_try_with(|figment| {
+    ///     let origin: String = figment
+    ///         .extract_inner("frontend_origin")
+    ///         .unwrap_or_else(|_| "https://www.acme.com".to_string());
+    ///     Ok(CorsOptions::default().allowed_origins(AllowedOrigins::some_exact(&[origin])))
+    /// }));
+    /// ```
+    pub fn fairing_try_with<F>(options: F) -> AdHoc
+    where
+        F: FnOnce(&Figment) -> Result<CorsOptions, Error> + Send + Sync + 'static,
+    {
+        AdHoc::try_on_ignite("CORS Configuration", |rocket| async move {
+            match options(rocket.figment()).and_then(|options| options.to_cors()) {
+                Ok(cors) => Ok(rocket.attach(cors)),
+                Err(err) => {
+                    error_!("CORS Fairing: Failed to create the fairing: {}", err);
+                    Err(rocket)
+                }
+            }
+        })
     }
-    Ok(())
 }
 
 #[rocket::async_trait]
@@ -104,47 +583,72 @@ impl rocket::fairing::Fairing for Cors {
         rocket::fairing::Info {
             name: "CORS",
             kind: rocket::fairing::Kind::Ignite
+                | rocket::fairing::Kind::Liftoff
                 | rocket::fairing::Kind::Request
                 | rocket::fairing::Kind::Response,
         }
     }
 
     async fn on_ignite(&self, rocket: rocket::Rocket<rocket::Build>) -> rocket::fairing::Result {
-        Ok(rocket.mount(
-            &self.fairing_route_base,
-            vec![fairing_route(self.fairing_route_rank)],
-        ))
+        let _level = logging::level(self.log_level);
+        let rocket = match rocket.state::<AttachedFairings>() {
+            Some(_) => rocket,
+            None => rocket.manage(AttachedFairings::default()),
+        };
+
+        let newly_attached = rocket
+            .state::<AttachedFairings>()
+            .expect("to be managed")
+            .0
+            .lock()
+            .expect("lock not to be poisoned")
+            .insert(self.id);
+        if !newly_attached {
+            error_!(
+                "CORS Fairing: This `Cors` fairing has already been attached. \
+                 Attach the fairing only once."
+            );
+            return Err(rocket);
+        }
+
+        if self.allowed_origins.is_all() && self.allowed_headers.is_all() {
+            warn_!(
+                "CORS Fairing: All origins and request headers are allowed. \
+                 This is not suitable for production."
+            );
+        }
+
+        Ok(rocket)
     }
 
-    async fn on_request(&self, request: &mut Request<'_>, _: &mut rocket::Data<'_>) {
-        let result = match validate(self, request) {
-            Ok(_) => CorsValidation::Success,
-            Err(err) => {
-                error_!("CORS Error: {}", err);
-                let status = err.status();
-                route_to_fairing_error_handler(self, status.code, request);
-                CorsValidation::Failure
-            }
-        };
+    async fn on_liftoff(&self, _: &rocket::Rocket<rocket::Orbit>) {
+        let _level = logging::level(self.log_level);
+        // Fairings share their name in Rocket's logs, so scoped ones are told apart by their scope
+        match self.fairing_scope {
+            Some(ref scope) => info_!("CORS policy for {}:", scope),
+            None => info_!("CORS policy:"),
+        }
+        for (name, value) in policy_summary(self) {
+            info_!("{}: {}", name, value);
+        }
+    }
 
-        let _ = request.local_cache(|| result);
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut rocket::Data<'_>) {
+        on_request_wrapper(self, request).await;
     }
 
     async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut rocket::Response<'r>) {
-        if let Err(err) = on_response_wrapper(self, request, response) {
-            error_!("Fairings on_response error: {}\nMost likely a bug", err);
-            response.set_status(Status::InternalServerError);
-            let _ = response.body();
-        }
+        on_response_wrapper(request, response).await;
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use rocket::http::{Method, Status};
+    use rocket::http::Method;
     use rocket::local::blocking::Client;
     use rocket::Rocket;
 
+    use super::{policy_summary, route_matches};
     use crate::{AllowedHeaders, AllowedOrigins, Cors, CorsOptions};
 
     const CORS_ROOT: &str = "/my_cors";
@@ -170,35 +674,124 @@ mod tests {
     }
 
     #[test]
-    #[allow(non_snake_case)]
-    fn FairingErrorRoute_returns_passed_in_status() {
-        let client = Client::tracked(rocket(make_cors_options())).expect("to not fail");
-        let request = client.get(format!("{}/403", CORS_ROOT));
-        let response = request.dispatch();
-        assert_eq!(Status::Forbidden, response.status());
+    fn routes_are_matched_by_method_and_path() {
+        let client = Client::tracked(rocket::build()).expect("to not fail");
+        let route = |method, uri| rocket::Route::new(method, uri, rocket::route::dummy_handler);
+
+        let request = client.get("/api/users/42?page=1");
+        assert!(route_matches(
+            &route(Method::Get, "/api/users/<id>"),
+            request.inner()
+        ));
+        assert!(route_matches(
+            &route(Method::Get, "/api/<path..>"),
+            request.inner()
+        ));
+        assert!(route_matches(
+            &route(Method::Get, "/api/users/42?<page>"),
+            request.inner()
+        ));
+        assert!(!route_matches(
+            &route(Method::Post, "/api/users/<id>"),
+            request.inner()
+        ));
+        assert!(!route_matches(
+            &route(Method::Get, "/api/users"),
+            request.inner()
+        ));
+        assert!(!route_matches(
+            &route(Method::Get, "/api/teams/<id>"),
+            request.inner()
+        ));
+        assert!(!route_matches(
+            &route(Method::Get, "/api/users/<id>/x"),
+            request.inner()
+        ));
+
+        let request = client.head("/api/users/42");
+        assert!(route_matches(
+            &route(Method::Get, "/api/users/<id>"),
+            request.inner()
+        ));
     }
 
     #[test]
-    #[allow(non_snake_case)]
-    fn FairingErrorRoute_returns_500_for_unknown_status() {
-        let client = Client::tracked(rocket(make_cors_options())).expect("to not fail");
-        let request = client.get(format!("{}/999", CORS_ROOT));
-        let response = request.dispatch();
-        assert_eq!(Status::InternalServerError, response.status());
+    fn policy_is_summarized() {
+        let summary = policy_summary(&make_cors_options());
+        assert_eq!(
+            summary,
+            vec![
+                ("origins", "1 exact".to_string()),
+                ("methods", "GET".to_string()),
+                ("headers", "Accept, Authorization".to_string()),
+                ("credentials", "true".to_string()),
+                ("max age", "none".to_string()),
+            ]
+        );
+
+        #[cfg(feature = "regex")]
+        {
+            let cors = CorsOptions::default()
+                .allowed_origins(AllowedOrigins::some(
+                    &["https://*.acme.com"],
+                    &["^https://(.+).acme.org$"],
+                ))
+                .max_age(Some(42))
+                .to_cors()
+                .expect("Not to fail");
+            let summary = policy_summary(&cors);
+            assert_eq!(
+                summary[0],
+                (
+                    "origins",
+                    "0 exact, 1 subdomain, regex ^https://(.+).acme.org$".to_string()
+                )
+            );
+            assert_eq!(summary[4], ("max age", "42s".to_string()));
+        }
+
+        let summary = policy_summary(&CorsOptions::default().to_cors().expect("Not to fail"));
+        assert_eq!(summary[0], ("origins", "all".to_string()));
+        assert_eq!(summary[2], ("headers", "all".to_string()));
     }
 
     #[rocket::async_test]
-    async fn error_route_is_mounted_on_ignite() {
+    async fn no_route_is_mounted_on_ignite() {
+        let mut other = make_cors_options();
+        other.fairing_scope = Some("/other".to_string());
         let rocket = rocket(make_cors_options())
+            .attach(other)
             .ignite()
             .await
             .expect("to ignite");
 
-        let expected_uri = format!("{}/<status>", CORS_ROOT);
-        let error_route = rocket
-            .routes()
-            .find(|r| r.method == Method::Get && r.uri.to_string() == expected_uri);
-        assert!(error_route.is_some());
+        assert_eq!(rocket.routes().count(), 0);
+    }
+
+    #[rocket::async_test]
+    async fn attaching_twice_fails_ignition() {
+        let cors = make_cors_options();
+        let rocket = rocket(cors.clone()).attach(cors);
+        let error = rocket.ignite().await.expect_err("to fail");
+        assert_matches!(error.kind(), rocket::error::ErrorKind::FailedFairings(_));
+    }
+
+    #[rocket::async_test]
+    async fn fairings_with_different_options_can_be_attached() {
+        let mut other = make_cors_options();
+        other.fairing_scope = Some("/other".to_string());
+
+        let rocket = rocket(make_cors_options()).attach(other);
+        let _ = rocket.ignite().await.expect("to ignite");
+    }
+
+    #[rocket::async_test]
+    async fn fairings_are_not_compared_by_their_options() {
+        let (cors, other) = (make_cors_options(), make_cors_options());
+        assert_eq!(cors, other);
+
+        let rocket = rocket(cors).attach(other);
+        let _ = rocket.ignite().await.expect("to ignite");
     }
 
     // Rest of the things can only be tested in integration tests
@@ -0,0 +1,455 @@
+//! The host that clients send requests to, when the application is behind a reverse proxy
+//!
+//! Behind a reverse proxy, the `Host` of a request is usually the address that the proxy forwards
+//! it to, rather than the public host of the application. Proxies report the original host in
+//! the `Forwarded` header, or in the `X-Forwarded-Host` header, as set by the
+//! [`ForwardedHeader`]. Any client can set these headers, so they are only read from the requests
+//! of [`TrustedProxies`].
+//!
+//! Give the trusted proxies to a [`TenantFairing`](crate::tenant::TenantFairing) to resolve
+//! policies for the forwarded host.
+//!
+//! # Example
+//! ```rust
+//! use std::net::{IpAddr, Ipv4Addr};
+//!
+//! use rocket_cors::forwarded::TrustedProxies;
+//!
+//! let proxies = TrustedProxies::some(&[IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))]);
+//! ```
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::net::IpAddr;
+
+use rocket::http::uri::Host;
+use rocket::Request;
+
+use crate::headers::is_token;
+
+/// The peers that are trusted to set the `Forwarded` or `X-Forwarded-Host` header of requests
+///
+/// The peer of a request is its [remote address](rocket::Request::remote), which is the address
+/// of the proxy when the application is behind one.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum TrustedProxies {
+    /// The headers are always ignored
+    #[default]
+    None,
+    /// The headers are trusted from every peer. Only use this if the application cannot be
+    /// reached without going through the proxy. Only the last element of the `Forwarded` header
+    /// is used, since the proxies in front of the peer are not known.
+    All,
+    /// The headers are trusted from the peers with these IP addresses, and from the proxies with
+    /// these addresses in front of them
+    Some(HashSet<IpAddr>),
+}
+
+/// The header that the trusted proxies report the host that clients sent requests to in
+///
+/// Only this header is read. The other one is ignored, since the proxies do not remove it from
+/// the requests of clients, who can set it to any host.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum ForwardedHeader {
+    /// The `host` parameter of the standard
+    /// [`Forwarded`](https://www.rfc-editor.org/rfc/rfc7239) header
+    #[default]
+    Forwarded,
+    /// The `X-Forwarded-Host` header
+    XForwardedHost,
+}
+
+impl TrustedProxies {
+    /// Trust the peers with the IP `addresses`
+    pub fn some(addresses: &[IpAddr]) -> Self {
+        TrustedProxies::Some(addresses.iter().copied().collect())
+    }
+
+    /// Returns whether the forwarding headers of `request` are trusted
+    pub fn trusts(&self, request: &Request<'_>) -> bool {
+        match self {
+            TrustedProxies::None => false,
+            TrustedProxies::All => true,
+            TrustedProxies::Some(addresses) => request
+                .remote()
+                .map_or(false, |remote| addresses.contains(&remote.ip())),
+        }
+    }
+
+    /// Returns the host that the client sent `request` to
+    ///
+    /// This is the host in the `header` of a request from a trusted proxy, and the `Host` of the
+    /// request otherwise. Forwarded hosts that are not valid, and `Forwarded` headers that are
+    /// malformed, are ignored.
+    ///
+    /// Proxies append to these headers, and the client can set them too, so they are read from
+    /// the right. The last element of the `Forwarded` header is the one added by the peer, and
+    /// elements to its left are only used if the proxy that added them is trusted, that is, if it
+    /// is the `for` node of the element to their right. Only the last value of the
+    /// `X-Forwarded-Host` header is used.
+    pub fn host<'a>(&self, request: &'a Request<'_>, header: ForwardedHeader) -> Option<Host<'a>> {
+        let forwarded = if self.trusts(request) {
+            match header {
+                ForwardedHeader::Forwarded => self.forwarded_host(request),
+                ForwardedHeader::XForwardedHost => request
+                    .headers()
+                    .get("X-Forwarded-Host")
+                    .flat_map(|value| value.split(','))
+                    .last()
+                    .map(str::trim)
+                    .filter(|host| !host.is_empty())
+                    .and_then(|host| Host::parse(host).ok()),
+            }
+        } else {
+            None
+        };
+        forwarded.or_else(|| request.host().cloned())
+    }
+
+    /// Returns the `host` parameter of the `Forwarded` header
+    fn forwarded_host<'a>(&self, request: &'a Request<'_>) -> Option<Host<'a>> {
+        let mut elements = vec![];
+        for value in request.headers().get("Forwarded") {
+            elements.extend(parse_forwarded(value)?);
+        }
+        let element = self.forwarded_element(&elements)?;
+        match forwarded_parameter(element, "host")? {
+            Cow::Borrowed(host) => Host::parse(host).ok(),
+            Cow::Owned(host) => Host::parse_owned(host.clone()).ok(),
+        }
+    }
+
+    /// Returns the element of the `Forwarded` header added by the trusted proxy closest to the
+    /// client, walking `elements` from the right
+    fn forwarded_element<'e, 'a>(&self, elements: &'e [Element<'a>]) -> Option<&'e Element<'a>> {
+        let mut elements = elements.iter().rev();
+        let mut element = elements.next()?;
+        if let TrustedProxies::Some(addresses) = self {
+            for previous in elements {
+                let proxied = forwarded_parameter(element, "for")
+                    .and_then(|node| node_address(node))
+                    .map_or(false, |address| addresses.contains(&address));
+                if !proxied {
+                    break;
+                }
+                element = previous;
+            }
+        }
+        Some(element)
+    }
+}
+
+/// An element of the `Forwarded` header, made of the names and values of its parameters
+type Element<'a> = Vec<(&'a str, Cow<'a, str>)>;
+
+/// Parses the elements of a `Forwarded` header, per
+/// [RFC 7239](https://www.rfc-editor.org/rfc/rfc7239#section-4), or returns `None` if it is
+/// malformed
+///
+/// Values can be quoted strings, which can contain commas, semicolons and escaped characters.
+fn parse_forwarded(value: &str) -> Option<Vec<Element<'_>>> {
+    let mut elements = vec![Element::new()];
+    let mut rest = value;
+    loop {
+        rest = rest.trim_start_matches([' ', '\t']);
+        let (name, value) = match rest.split_once('=') {
+            Some((name, value)) if is_token(name) => (name, value),
+            // Lists can have empty elements
+            _ if rest.is_empty() || rest.starts_with(',') => ("", rest),
+            _ => return None,
+        };
+        if !name.is_empty() {
+            let (value, remainder) = match value.strip_prefix('"') {
+                Some(quoted) => parse_quoted(quoted)?,
+                None => {
+                    let end = value.find([';', ',', ' ', '\t']).unwrap_or(value.len());
+                    if value[..end].contains('"') {
+                        return None;
+                    }
+                    (Cow::Borrowed(&value[..end]), &value[end..])
+                }
+            };
+            elements.last_mut()?.push((name, value));
+            rest = remainder.trim_start_matches([' ', '\t']);
+        } else {
+            rest = value;
+        }
+
+        let mut chars = rest.chars();
+        match chars.next() {
+            None => break,
+            Some(';') => {}
+            Some(',') => elements.push(Element::new()),
+            Some(_) => return None,
+        }
+        rest = chars.as_str();
+    }
+    elements.retain(|element| !element.is_empty());
+    Some(elements)
+}
+
+/// Parses a quoted string, after its opening quote, into its unescaped value and the rest of
+/// `quoted` after its closing quote, or returns `None` if it is not closed
+fn parse_quoted(quoted: &str) -> Option<(Cow<'_, str>, &str)> {
+    let mut unescaped: Option<String> = None;
+    let mut chars = quoted.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => {
+                let value = match unescaped {
+                    Some(unescaped) => Cow::Owned(unescaped),
+                    None => Cow::Borrowed(&quoted[..index]),
+                };
+                return Some((value, &quoted[index + 1..]));
+            }
+            '\\' => {
+                let (_, escaped) = chars.next()?;
+                unescaped
+                    .get_or_insert_with(|| quoted[..index].to_string())
+                    .push(escaped);
+            }
+            c => {
+                if let Some(unescaped) = unescaped.as_mut() {
+                    unescaped.push(c);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Returns the value of the parameter `name` of an element of the `Forwarded` header
+fn forwarded_parameter<'e, 'a>(element: &'e Element<'a>, name: &str) -> Option<&'e Cow<'a, str>> {
+    element
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value)
+}
+
+/// Returns the IP address of a node of the `Forwarded` header, such as `192.0.2.60:4711` or
+/// `[2001:db8::1]`, or `None` if it is obfuscated or `unknown`
+fn node_address(node: &str) -> Option<IpAddr> {
+    let address = match node.strip_prefix('[') {
+        Some(node) => node.split(']').next()?,
+        None => node.split(':').next()?,
+    };
+    address.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    use rocket::http::Header;
+    use rocket::local::blocking::Client;
+
+    use super::*;
+
+    #[test]
+    fn forwarded_parameters_are_parsed() {
+        let elements = parse_forwarded(r#"for=192.0.2.60;Proto=https;host="api.acme.com:8443""#)
+            .expect("to be valid");
+        assert_eq!(elements.len(), 1);
+        let element = &elements[0];
+        assert_eq!(
+            forwarded_parameter(element, "host").map(|host| &**host),
+            Some("api.acme.com:8443")
+        );
+        assert_eq!(
+            forwarded_parameter(element, "proto").map(|proto| &**proto),
+            Some("https")
+        );
+        assert_eq!(forwarded_parameter(element, "by"), None);
+
+        assert_eq!(
+            node_address("192.0.2.60:4711"),
+            Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 60)))
+        );
+        assert_eq!(
+            node_address("[2001:db8::1]:4711"),
+            "2001:db8::1".parse().ok()
+        );
+        assert_eq!(node_address("unknown"), None);
+        assert_eq!(node_address("_hidden"), None);
+    }
+
+    #[test]
+    fn quoted_forwarded_values_are_parsed() {
+        let elements = parse_forwarded(
+            r#"for="[2001:db8::1]:4711";host="evil.com, for=10.0.0.2;host=x", host="a\"b", , for=_x"#,
+        )
+        .expect("to be valid");
+        let hosts: Vec<_> = elements
+            .iter()
+            .map(|element| forwarded_parameter(element, "host").map(|host| &**host))
+            .collect();
+        assert_eq!(
+            hosts,
+            vec![Some("evil.com, for=10.0.0.2;host=x"), Some("a\"b"), None]
+        );
+        assert_eq!(
+            forwarded_parameter(&elements[0], "for").map(|node| &**node),
+            Some("[2001:db8::1]:4711")
+        );
+
+        for malformed in [
+            r#"host="api.acme.com"#,
+            "host",
+            "host=api.acme.com;;",
+            r#"host=api"acme.com"#,
+            "host=api.acme.com proto=https",
+        ] {
+            assert_eq!(parse_forwarded(malformed), None, "{}", malformed);
+        }
+    }
+
+    #[test]
+    fn forwarded_headers_are_only_read_from_trusted_proxies() {
+        let client = Client::untracked(rocket::build()).expect("valid rocket instance");
+        let proxy = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let mut request = client
+            .get("/")
+            .header(Header::new("X-Forwarded-Host", "api.acme.com"));
+        request.inner_mut().set_remote(SocketAddr::new(proxy, 1234));
+        let host = Host::parse("localhost:8000").expect("to be valid");
+        request.inner_mut().set_host(host);
+        let request = request.inner();
+
+        let host = |proxies: &TrustedProxies| {
+            proxies
+                .host(request, ForwardedHeader::XForwardedHost)
+                .map(|host| host.domain().as_str().to_string())
+        };
+        for proxies in [TrustedProxies::All, TrustedProxies::some(&[proxy])] {
+            assert_eq!(host(&proxies), Some("api.acme.com".to_string()));
+        }
+
+        let untrusted = TrustedProxies::some(&[IpAddr::V4(Ipv4Addr::LOCALHOST)]);
+        for proxies in [TrustedProxies::None, untrusted] {
+            assert_eq!(host(&proxies), Some("localhost".to_string()));
+        }
+    }
+
+    /// Returns the host forwarded in `header` by a request from `proxy` with the `headers`
+    fn forwarded_host(
+        proxies: &TrustedProxies,
+        header: ForwardedHeader,
+        proxy: IpAddr,
+        headers: &[(&str, &str)],
+    ) -> String {
+        let client = Client::untracked(rocket::build()).expect("valid rocket instance");
+        let mut request = client.get("/");
+        for (name, value) in headers {
+            request.add_header(Header::new(name.to_string(), value.to_string()));
+        }
+        request.inner_mut().set_remote(SocketAddr::new(proxy, 1234));
+        request
+            .inner_mut()
+            .set_host(Host::parse("localhost").expect("to be valid"));
+        let host = proxies
+            .host(request.inner(), header)
+            .expect("to have a host");
+        host.domain().as_str().to_string()
+    }
+
+    #[test]
+    fn only_the_configured_header_is_read() {
+        let proxy = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let headers = [("X-Forwarded-Host", "evil.com")];
+        let host = forwarded_host(
+            &TrustedProxies::All,
+            ForwardedHeader::Forwarded,
+            proxy,
+            &headers,
+        );
+        assert_eq!(host, "localhost");
+
+        let headers = [("Forwarded", "host=evil.com")];
+        let host = forwarded_host(
+            &TrustedProxies::All,
+            ForwardedHeader::XForwardedHost,
+            proxy,
+            &headers,
+        );
+        assert_eq!(host, "localhost");
+    }
+
+    #[test]
+    fn headers_set_by_clients_are_ignored() {
+        let proxy = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        for proxies in [TrustedProxies::All, TrustedProxies::some(&[proxy])] {
+            // The proxy appended to the headers sent by the client
+            let host = forwarded_host(
+                &proxies,
+                ForwardedHeader::XForwardedHost,
+                proxy,
+                &[("X-Forwarded-Host", "evil.com, api.acme.com")],
+            );
+            assert_eq!(host, "api.acme.com");
+
+            let host = forwarded_host(
+                &proxies,
+                ForwardedHeader::Forwarded,
+                proxy,
+                &[(
+                    "Forwarded",
+                    "host=evil.com, for=192.0.2.60;host=api.acme.com",
+                )],
+            );
+            assert_eq!(host, "api.acme.com");
+
+            // The client quoted a comma to make up an element of its own
+            let host = forwarded_host(
+                &proxies,
+                ForwardedHeader::Forwarded,
+                proxy,
+                &[(
+                    "Forwarded",
+                    r#"host="x, for=10.0.0.1;host=evil.com", for=192.0.2.60;host=api.acme.com"#,
+                )],
+            );
+            assert_eq!(host, "api.acme.com");
+
+            // The proxy added its own header line
+            let host = forwarded_host(
+                &proxies,
+                ForwardedHeader::Forwarded,
+                proxy,
+                &[
+                    ("Forwarded", "host=evil.com"),
+                    ("Forwarded", "for=192.0.2.60;host=api.acme.com"),
+                ],
+            );
+            assert_eq!(host, "api.acme.com");
+        }
+    }
+
+    #[test]
+    fn forwarded_hosts_are_read_through_trusted_proxies() {
+        let edge = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+        let proxy = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let forwarded = [(
+            "Forwarded",
+            "host=evil.com, for=192.0.2.60;host=api.acme.com, for=10.0.0.2;host=internal",
+        )];
+        let host = |proxies: &TrustedProxies| {
+            forwarded_host(proxies, ForwardedHeader::Forwarded, proxy, &forwarded)
+        };
+
+        assert_eq!(host(&TrustedProxies::some(&[edge, proxy])), "api.acme.com");
+        assert_eq!(host(&TrustedProxies::some(&[proxy])), "internal");
+        assert_eq!(host(&TrustedProxies::All), "internal");
+    }
+
+    #[test]
+    fn malformed_forwarded_headers_are_ignored() {
+        let proxy = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let host = forwarded_host(
+            &TrustedProxies::All,
+            ForwardedHeader::Forwarded,
+            proxy,
+            &[("Forwarded", r#"host="api.acme.com"#)],
+        );
+        assert_eq!(host, "localhost");
+    }
+}
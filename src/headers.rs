@@ -1,6 +1,7 @@
 //! CORS specific Request Headers
 
 use std::collections::HashSet;
+use std::error;
 use std::fmt;
 use std::ops::Deref;
 use std::str::FromStr;
@@ -33,6 +34,21 @@ impl fmt::Display for HeaderFieldName {
     }
 }
 
+impl HeaderFieldName {
+    /// Create a header field name, or returns
+    /// [`Error::InvalidHeaderFieldName`](crate::Error::InvalidHeaderFieldName) if `name` is not a
+    /// valid [token](https://tools.ietf.org/html/rfc7230#section-3.2.6)
+    ///
+    /// The `From` implementations do not check the name.
+    pub fn new<S: Into<String>>(name: S) -> Result<Self, crate::Error> {
+        let name = name.into();
+        if !is_token(&name) {
+            return Err(crate::Error::InvalidHeaderFieldName(name));
+        }
+        Ok(HeaderFieldName(From::from(name)))
+    }
+}
+
 impl<'a> From<&'a str> for HeaderFieldName {
     fn from(s: &'a str) -> Self {
         HeaderFieldName(From::from(s))
@@ -45,17 +61,45 @@ impl From<String> for HeaderFieldName {
     }
 }
 
+/// Fails unless `s` is a valid header field name, like [`HeaderFieldName::new`]
 impl FromStr for HeaderFieldName {
-    type Err = <String as FromStr>::Err;
+    type Err = crate::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(HeaderFieldName(FromStr::from_str(s)?))
+        Self::new(s)
     }
 }
 
 /// A set of case insensitive header names
 pub type HeaderFieldNamesSet = HashSet<HeaderFieldName>;
 
+/// Returns whether the string is a valid
+/// [token](https://tools.ietf.org/html/rfc7230#section-3.2.6), which is what header field names
+/// and methods are made of.
+pub(crate) fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes().all(|b| {
+            b.is_ascii_alphanumeric()
+                || matches!(
+                    b,
+                    b'!' | b'#'
+                        | b'$'
+                        | b'%'
+                        | b'&'
+                        | b'\''
+                        | b'*'
+                        | b'+'
+                        | b'-'
+                        | b'.'
+                        | b'^'
+                        | b'_'
+                        | b'`'
+                        | b'|'
+                        | b'~'
+                )
+        })
+}
+
 /// The `Origin` request header used in CORS
 ///
 /// You can use this as a rocket [Request Guard](https://rocket.rs/guide/requests/#request-guards)
@@ -77,7 +121,11 @@ impl Origin {
     /// [ASCII serialization](https://html.spec.whatwg.org/multipage/#ascii-serialisation-of-an-origin)
     /// of this origin.
     pub fn ascii_serialization(&self) -> String {
-        self.to_string()
+        match self {
+            Origin::Null => "null".to_string(),
+            Origin::Parsed(ref parsed) => parsed.ascii_serialization(),
+            Origin::Opaque(ref opaque) => opaque.clone(),
+        }
     }
 
     /// Returns whether the origin was parsed as non-opaque
@@ -89,6 +137,33 @@ impl Origin {
         }
     }
 
+    /// Returns the scheme of the origin, such as `https`, or `None` for `null` and opaque origins
+    pub fn scheme(&self) -> Option<&str> {
+        match self {
+            Origin::Parsed(url::Origin::Tuple(ref scheme, _, _)) => Some(scheme),
+            _ => None,
+        }
+    }
+
+    /// Returns the host of the origin, or `None` for `null` and opaque origins
+    pub fn host(&self) -> Option<&url::Host<String>> {
+        match self {
+            Origin::Parsed(url::Origin::Tuple(_, ref host, _)) => Some(host),
+            _ => None,
+        }
+    }
+
+    /// Returns the port of the origin, or `None` for `null` and opaque origins
+    ///
+    /// The default port of the scheme is returned if the origin does not have a port, such as
+    /// `443` for `https://www.acme.com`.
+    pub fn port(&self) -> Option<u16> {
+        match self {
+            Origin::Parsed(url::Origin::Tuple(_, _, port)) => Some(*port),
+            _ => None,
+        }
+    }
+
     /// Derives an instance of `Self` from the incoming request metadata.
     ///
     /// If the derivation is successful, an outcome of `Success` is returned. If
@@ -112,7 +187,7 @@ impl FromStr for Origin {
     type Err = crate::Error;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        if input.to_lowercase() == "null" {
+        if input.eq_ignore_ascii_case("null") {
             Ok(Origin::Null)
         } else {
             match crate::to_origin(input)? {
@@ -133,6 +208,24 @@ impl fmt::Display for Origin {
     }
 }
 
+/// `Origin` is serialized as its
+/// [ASCII serialization](https://html.spec.whatwg.org/multipage/#ascii-serialisation-of-an-origin)
+#[cfg(feature = "serialization")]
+impl serde::Serialize for Origin {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.ascii_serialization())
+    }
+}
+
+/// `Origin` is deserialized from a string, like [`Origin::from_str`]
+#[cfg(feature = "serialization")]
+impl<'de> serde::Deserialize<'de> for Origin {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let origin = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Self::from_str(&origin).map_err(serde::de::Error::custom)
+    }
+}
+
 #[rocket::async_trait]
 impl<'r> FromRequest<'r> for Origin {
     type Error = crate::Error;
@@ -164,7 +257,7 @@ impl AccessControlRequestMethod {
         match request.headers().get_one("Access-Control-Request-Method") {
             Some(request_method) => match Self::from_str(request_method) {
                 Ok(request_method) => Outcome::Success(request_method),
-                Err(_) => Outcome::Error((Status::BadRequest, crate::Error::BadRequestMethod)),
+                Err(e) => Outcome::Error((Status::BadRequest, crate::Error::BadRequestMethod(e))),
             },
             None => Outcome::Forward(Status::default()),
         }
@@ -172,7 +265,7 @@ impl AccessControlRequestMethod {
 }
 
 impl FromStr for AccessControlRequestMethod {
-    type Err = ();
+    type Err = crate::MethodParseError;
 
     fn from_str(method: &str) -> Result<Self, Self::Err> {
         Ok(AccessControlRequestMethod(crate::Method::from_str(method)?))
@@ -190,6 +283,45 @@ impl<'r> FromRequest<'r> for AccessControlRequestMethod {
     }
 }
 
+/// The longest header name accepted by [`AccessControlRequestHeaders::parse_strict`], in bytes
+pub const MAX_REQUEST_HEADER_NAME_LENGTH: usize = 256;
+
+/// The error when strictly parsing an `Access-Control-Request-Headers` request header that is
+/// malformed
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RequestHeadersParseError {
+    /// The list has an empty entry, such as in `X-Foo,,X-Bar` or `X-Foo,`
+    EmptyEntry,
+    /// A header name is listed more than once, ignoring case. Holds the name.
+    Duplicate(String),
+    /// An entry is not a valid [token](https://tools.ietf.org/html/rfc7230#section-3.2.6).
+    /// Holds the entry.
+    InvalidName(String),
+    /// A header name is longer than [`MAX_REQUEST_HEADER_NAME_LENGTH`]. Holds its length.
+    TooLong(usize),
+}
+
+impl fmt::Display for RequestHeadersParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestHeadersParseError::EmptyEntry => write!(f, "The list has an empty entry"),
+            RequestHeadersParseError::Duplicate(name) => {
+                write!(f, "'{}' is listed more than once", name)
+            }
+            RequestHeadersParseError::InvalidName(name) => {
+                write!(f, "'{}' is not a valid header name", name)
+            }
+            RequestHeadersParseError::TooLong(length) => write!(
+                f,
+                "A header name is {} bytes long, longer than the limit of {} bytes",
+                length, MAX_REQUEST_HEADER_NAME_LENGTH
+            ),
+        }
+    }
+}
+
+impl error::Error for RequestHeadersParseError {}
+
 /// The `Access-Control-Request-Headers` request header
 ///
 /// You can use this as a rocket [Request Guard](https://rocket.rs/guide/requests/#request-guards)
@@ -237,6 +369,37 @@ impl FromStr for AccessControlRequestHeaders {
     }
 }
 
+impl AccessControlRequestHeaders {
+    /// Parses the header strictly, as with
+    /// [`CorsOptions::strict_request_headers`](crate::CorsOptions::strict_request_headers)
+    ///
+    /// Unlike `from_str`, which accepts anything, each entry must be a valid header name of at
+    /// most [`MAX_REQUEST_HEADER_NAME_LENGTH`] bytes, and be listed once. Whitespace around the
+    /// entries is allowed. An empty header is an empty list.
+    pub fn parse_strict(headers: &str) -> Result<Self, RequestHeadersParseError> {
+        let mut set = HashSet::new();
+        if headers.trim().is_empty() {
+            return Ok(AccessControlRequestHeaders(set));
+        }
+
+        for header in headers.split(',').map(str::trim) {
+            if header.is_empty() {
+                return Err(RequestHeadersParseError::EmptyEntry);
+            }
+            if header.len() > MAX_REQUEST_HEADER_NAME_LENGTH {
+                return Err(RequestHeadersParseError::TooLong(header.len()));
+            }
+            if !is_token(header) {
+                return Err(RequestHeadersParseError::InvalidName(header.to_string()));
+            }
+            if !set.insert(HeaderFieldName::from(header)) {
+                return Err(RequestHeadersParseError::Duplicate(header.to_string()));
+            }
+        }
+        Ok(AccessControlRequestHeaders(set))
+    }
+}
+
 #[rocket::async_trait]
 impl<'r> FromRequest<'r> for AccessControlRequestHeaders {
     type Error = crate::Error;
@@ -270,6 +433,95 @@ mod tests {
         Client::tracked(rocket).expect("valid rocket instance")
     }
 
+    // `Origin` tests
+
+    #[test]
+    fn origin_components_are_returned() {
+        let origin = not_err!(Origin::from_str("https://www.acme.com"));
+        assert_eq!(origin.scheme(), Some("https"));
+        assert_eq!(
+            origin.host(),
+            Some(&url::Host::Domain("www.acme.com".to_string()))
+        );
+        assert_eq!(origin.port(), Some(443));
+
+        let origin = not_err!(Origin::from_str("http://127.0.0.1:8000"));
+        assert_eq!(origin.scheme(), Some("http"));
+        assert_eq!(
+            origin.host(),
+            Some(&url::Host::Ipv4("127.0.0.1".parse().unwrap()))
+        );
+        assert_eq!(origin.port(), Some(8000));
+
+        for origin in ["null", "file:///etc/passwd"] {
+            let origin = not_err!(Origin::from_str(origin));
+            assert_eq!(origin.scheme(), None);
+            assert_eq!(origin.host(), None);
+            assert_eq!(origin.port(), None);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serialization")]
+    fn origin_serialization_roundtrips() {
+        for origin in ["https://www.acme.com", "null", "moz-extension://abcdef"] {
+            let parsed = not_err!(Origin::from_str(origin));
+            let json = serde_json::to_string(&parsed).unwrap();
+            assert_eq!(json, format!("\"{}\"", origin));
+            let deserialized: Origin = serde_json::from_str(&json).unwrap();
+            assert_eq!(deserialized, parsed);
+        }
+
+        assert!(serde_json::from_str::<Origin>("\"not an origin\"").is_err());
+    }
+
+    // `HeaderFieldName` tests
+
+    #[test]
+    fn header_field_names_are_checked() {
+        let name = not_err!(HeaderFieldName::new("X-Custom"));
+        assert_eq!(name, HeaderFieldName::from("x-custom"));
+        let _ = not_err!(HeaderFieldName::from_str("Authorization"));
+
+        assert_matches!(
+            HeaderFieldName::new("not a header!!"),
+            Err(crate::Error::InvalidHeaderFieldName(_))
+        );
+        assert_matches!(
+            HeaderFieldName::from_str(""),
+            Err(crate::Error::InvalidHeaderFieldName(_))
+        );
+    }
+
+    // `is_token` tests
+
+    #[test]
+    fn tokens_are_validated() {
+        for valid in &[
+            "Authorization",
+            "X-Custom-Header",
+            "x_custom",
+            "!#$%&'*+-.^_`|~09",
+        ] {
+            assert!(is_token(valid), "{} should be a valid token", valid);
+        }
+
+        for invalid in &[
+            "",
+            "Oops Header",
+            "X-A,X-B",
+            "X:Header",
+            "Ünicode",
+            "(comment)",
+        ] {
+            assert!(
+                !is_token(invalid),
+                "{} should not be a valid token",
+                invalid
+            );
+        }
+    }
+
     // `Origin::from_str` tests
 
     #[test]
@@ -288,6 +540,20 @@ mod tests {
         assert_eq!(parsed.ascii_serialization(), expected);
     }
 
+    #[test]
+    fn origin_parsing_normalizes_default_ports_and_trailing_dots() {
+        for (url, expected) in [
+            ("https://foo.bar.xyz:443", "https://foo.bar.xyz"),
+            ("http://foo.bar.xyz:80", "http://foo.bar.xyz"),
+            ("https://foo.bar.xyz.", "https://foo.bar.xyz"),
+            ("http://foo.bar.xyz.:8080", "http://foo.bar.xyz:8080"),
+            ("https://foo.bar.xyz:80", "https://foo.bar.xyz:80"),
+        ] {
+            let parsed = not_err!(Origin::from_str(url));
+            assert_eq!(parsed.ascii_serialization(), expected);
+        }
+    }
+
     #[test]
     #[should_panic(expected = "BadOrigin")]
     fn origin_parsing_disallows_invalid_origins() {
@@ -344,21 +610,25 @@ mod tests {
     #[test]
     fn request_method_conversion() {
         let method = "POST";
-        let parsed_method = not_err!(AccessControlRequestMethod::from_str(method));
-        assert_matches!(
-            parsed_method,
-            AccessControlRequestMethod(crate::Method(rocket::http::Method::Post))
-        );
+        let AccessControlRequestMethod(parsed_method) =
+            not_err!(AccessControlRequestMethod::from_str(method));
+        assert_eq!(parsed_method, rocket::http::Method::Post);
 
         let method = "options";
-        let parsed_method = not_err!(AccessControlRequestMethod::from_str(method));
-        assert_matches!(
-            parsed_method,
-            AccessControlRequestMethod(crate::Method(rocket::http::Method::Options))
-        );
-
-        let method = "INVALID";
-        is_err!(AccessControlRequestMethod::from_str(method));
+        let AccessControlRequestMethod(parsed_method) =
+            not_err!(AccessControlRequestMethod::from_str(method));
+        assert_eq!(parsed_method, rocket::http::Method::Options);
+
+        let method = "PROPFIND";
+        let AccessControlRequestMethod(parsed_method) =
+            not_err!(AccessControlRequestMethod::from_str(method));
+        assert_eq!(parsed_method.as_str(), "PROPFIND");
+        assert_eq!(parsed_method.as_rocket(), None);
+
+        let method = "IN VALID";
+        let error = is_err!(AccessControlRequestMethod::from_str(method));
+        assert_eq!(error, crate::MethodParseError("IN VALID".to_string()));
+        assert_eq!(error.to_string(), "'IN VALID' is not a valid HTTP method");
     }
 
     #[test]
@@ -375,6 +645,21 @@ mod tests {
         let parsed_header = assert_matches!(outcome, Outcome::Success(s), s);
         let AccessControlRequestMethod(parsed_method) = parsed_header;
         assert_eq!("GET", parsed_method.as_str());
+
+        let mut request = client.get("/");
+        request.add_header(Header::new(
+            ACCESS_CONTROL_REQUEST_METHOD.as_str(),
+            "IN VALID",
+        ));
+        let outcome = AccessControlRequestMethod::from_request_sync(request.inner());
+        let error = assert_matches!(outcome, Outcome::Error((_, error)), error);
+        assert_matches!(
+            error,
+            crate::Error::BadRequestMethod(crate::MethodParseError(method)),
+            {
+                assert_eq!(method, "IN VALID");
+            }
+        );
     }
 
     #[test]
@@ -387,6 +672,35 @@ mod tests {
         assert_eq!(actual_headers, expected_headers);
     }
 
+    #[test]
+    fn request_headers_are_parsed_strictly() {
+        let parsed = not_err!(AccessControlRequestHeaders::parse_strict(" foo ,bar"));
+        assert_eq!(parsed.0.len(), 2);
+        assert!(not_err!(AccessControlRequestHeaders::parse_strict(" "))
+            .0
+            .is_empty());
+
+        let long = "x".repeat(MAX_REQUEST_HEADER_NAME_LENGTH + 1);
+        for (headers, expected) in [
+            ("foo,,bar", RequestHeadersParseError::EmptyEntry),
+            ("foo, ", RequestHeadersParseError::EmptyEntry),
+            (
+                "foo, Foo",
+                RequestHeadersParseError::Duplicate("Foo".to_string()),
+            ),
+            (
+                "foo, b@r",
+                RequestHeadersParseError::InvalidName("b@r".to_string()),
+            ),
+            (&long, RequestHeadersParseError::TooLong(long.len())),
+        ] {
+            assert_eq!(
+                AccessControlRequestHeaders::parse_strict(headers),
+                Err(expected)
+            );
+        }
+    }
+
     #[test]
     fn request_headers_parsing() {
         let client = make_client();
@@ -0,0 +1,133 @@
+//! Internal logging macros
+//!
+//! By default, these forward to the `log` crate, like Rocket's own logging macros. With the
+//! `tracing` feature enabled, they emit `tracing` events instead, and CORS validation is wrapped in
+//! a `cors` span.
+//!
+//! All the records use the `rocket_cors` target. While a `Cors` is at work, such as when it
+//! validates a request, records are skipped if their level is above the
+//! [`CorsOptions::log_level`](crate::CorsOptions::log_level) of that `Cors`, which is set for the
+//! current thread with [`level`]. Key-value fields can be given before the message, separated from
+//! it by a `;`:
+//!
+//! ```text
+//! error_!(origin = "https://www.acme.com"; "CORS Error: {}", err);
+//! ```
+
+use std::cell::Cell;
+
+use log::{Level, LevelFilter};
+
+/// The target of the log records, and of the `tracing` events and spans
+pub(crate) const TARGET: &str = "rocket_cors";
+
+thread_local! {
+    /// The most verbose level that is logged on this thread
+    static MAX_LEVEL: Cell<LevelFilter> = const { Cell::new(LevelFilter::Trace) };
+}
+
+/// Restores the level that was logged before [`level`] was called when dropped
+#[must_use]
+pub(crate) struct LevelGuard(Option<LevelFilter>);
+
+impl Drop for LevelGuard {
+    fn drop(&mut self) {
+        if let Some(previous) = self.0 {
+            MAX_LEVEL.with(|max| max.set(previous));
+        }
+    }
+}
+
+/// Sets the most verbose level that is logged on this thread to `level`, if any, until the
+/// returned guard is dropped
+///
+/// The guard must not be held across an `.await`, since the task could then resume on another
+/// thread.
+pub(crate) fn level(level: Option<LevelFilter>) -> LevelGuard {
+    LevelGuard(level.map(|level| MAX_LEVEL.with(|max| max.replace(level))))
+}
+
+/// Returns whether records of `level` are logged
+pub(crate) fn enabled(level: Level) -> bool {
+    level <= MAX_LEVEL.with(Cell::get)
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! log_ {
+    ($level:ident, $_tracing_level:ident, $($key:ident = $value:expr),+; $($arg:tt)+) => {
+        if $crate::logging::enabled(::log::Level::$level) {
+            ::log::log!(
+                target: $crate::logging::TARGET,
+                ::log::Level::$level,
+                $($key = $value),+;
+                $($arg)+
+            )
+        }
+    };
+    ($level:ident, $_tracing_level:ident, $($arg:tt)+) => {
+        if $crate::logging::enabled(::log::Level::$level) {
+            ::log::log!(target: $crate::logging::TARGET, ::log::Level::$level, $($arg)+)
+        }
+    };
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! log_ {
+    ($level:ident, $tracing_level:ident, $($key:ident = $value:expr),+; $($arg:tt)+) => {
+        if $crate::logging::enabled(::log::Level::$level) {
+            ::tracing::event!(
+                target: $crate::logging::TARGET,
+                ::tracing::Level::$tracing_level,
+                $($key = $value),+,
+                $($arg)+
+            )
+        }
+    };
+    ($level:ident, $tracing_level:ident, $($arg:tt)+) => {
+        if $crate::logging::enabled(::log::Level::$level) {
+            ::tracing::event!(
+                target: $crate::logging::TARGET,
+                ::tracing::Level::$tracing_level,
+                $($arg)+
+            )
+        }
+    };
+}
+
+macro_rules! info_ {
+    ($($arg:tt)+) => (log_!(Info, INFO, $($arg)+))
+}
+
+macro_rules! debug_ {
+    ($($arg:tt)+) => (log_!(Debug, DEBUG, $($arg)+))
+}
+
+macro_rules! warn_ {
+    ($($arg:tt)+) => (log_!(Warn, WARN, $($arg)+))
+}
+
+macro_rules! error_ {
+    ($($arg:tt)+) => (log_!(Error, ERROR, $($arg)+))
+}
+
+/// Enters a `cors` span with the `origin` and `method` of the request, which is exited when the
+/// returned guard is dropped. Does nothing without the `tracing` feature.
+#[cfg(not(feature = "tracing"))]
+macro_rules! validation_span {
+    ($request:expr) => {
+        ()
+    };
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! validation_span {
+    ($request:expr) => {
+        ::tracing::info_span!(
+            target: $crate::logging::TARGET,
+            "cors",
+            origin = $request.header("Origin"),
+            method = %$request.method(),
+        )
+        .entered()
+    };
+}
@@ -0,0 +1,90 @@
+//! Metrics for CORS decisions
+//!
+//! Implement [`CorsMetrics`] and register it with [`CorsOptions::metrics`] to be notified of every
+//! CORS request that is validated, in all modes of operation.
+//!
+//! # Example
+//! ```rust
+//! use std::sync::atomic::{AtomicUsize, Ordering};
+//! use std::sync::Arc;
+//!
+//! use rocket_cors::metrics::{CorsMetrics, RequestKind};
+//! use rocket_cors::{CorsOptions, Error};
+//!
+//! #[derive(Debug, Default)]
+//! struct Counters {
+//!     allowed: AtomicUsize,
+//!     denied: AtomicUsize,
+//! }
+//!
+//! impl CorsMetrics for Counters {
+//!     fn allowed(&self, _: RequestKind) {
+//!         let _ = self.allowed.fetch_add(1, Ordering::Relaxed);
+//!     }
+//!
+//!     fn denied(&self, _: RequestKind, _: &Error) {
+//!         let _ = self.denied.fetch_add(1, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! let counters = Arc::new(Counters::default());
+//! let cors = CorsOptions::default()
+//!     .metrics(counters.clone())
+//!     .to_cors()
+//!     .expect("To not fail");
+//! ```
+//!
+//! [`CorsOptions::metrics`]: crate::CorsOptions::metrics
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::Error;
+
+#[cfg(feature = "prometheus")]
+mod prometheus;
+
+#[cfg(feature = "prometheus")]
+pub use self::prometheus::PrometheusRecorder;
+
+/// The kind of a CORS request
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum RequestKind {
+    /// A pre-flight `OPTIONS` request
+    Preflight,
+    /// An actual request
+    Actual,
+}
+
+/// Receives the decisions made when validating CORS requests
+///
+/// Requests without an `Origin` header are not CORS requests and are not reported.
+///
+/// All methods do nothing by default, so you only need to implement the ones you are interested
+/// in. They are called on the request path, so they should be fast and must not block.
+pub trait CorsMetrics: fmt::Debug + Send + Sync {
+    /// Called for every pre-flight request with the time taken to validate it, whether it was
+    /// allowed or not
+    fn preflight(&self, elapsed: Duration) {
+        let _ = elapsed;
+    }
+
+    /// Called when a CORS request is allowed
+    fn allowed(&self, kind: RequestKind) {
+        let _ = kind;
+    }
+
+    /// Called when a CORS request is denied, with the error it was denied for
+    fn denied(&self, kind: RequestKind, error: &Error) {
+        let _ = (kind, error);
+    }
+
+    /// Called when an origin is looked up in the cache set with
+    /// [`CorsOptions::origin_cache_size`], with whether it was found, and the number of origins in
+    /// the cache afterwards
+    ///
+    /// [`CorsOptions::origin_cache_size`]: crate::CorsOptions::origin_cache_size
+    fn origin_cache(&self, hit: bool, size: usize) {
+        let _ = (hit, size);
+    }
+}
@@ -0,0 +1,262 @@
+//! Prometheus metrics, enabled with the `prometheus` feature
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use prometheus_client::encoding::text;
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::family::Family;
+use prometheus_client::metrics::gauge::Gauge;
+use prometheus_client::metrics::histogram::{exponential_buckets, Histogram};
+use prometheus_client::registry::Registry;
+use rocket::http::{ContentType, Method};
+use rocket::{route, Data, Request, Route};
+
+use super::{CorsMetrics, RequestKind};
+use crate::Error;
+
+type Labels = [(&'static str, &'static str); 2];
+type DeniedLabels = [(&'static str, String); 2];
+
+/// A [`CorsMetrics`] implementation that records
+/// [Prometheus](https://prometheus.io/) metrics
+///
+/// The following metrics are recorded:
+///
+/// - `rocket_cors_requests_total`: A counter of CORS requests, labelled by `kind` (`preflight` or
+///   `actual`) and `outcome` (`allowed` or `denied`)
+/// - `rocket_cors_denied_total`: A counter of denied CORS requests, labelled by `kind` and the
+///   `reason` they were denied for, such as `origin_not_allowed`
+/// - `rocket_cors_preflight_duration_seconds`: A histogram of the time taken to validate
+///   pre-flight requests
+/// - `rocket_cors_origin_cache_lookups_total`: A counter of lookups in the cache set with
+///   [`CorsOptions::origin_cache_size`](crate::CorsOptions::origin_cache_size), labelled by
+///   `result` (`hit` or `miss`)
+/// - `rocket_cors_origin_cache_size`: A gauge of the number of origins in that cache
+///
+/// The metrics can be served in the
+/// [OpenMetrics text format](https://github.com/OpenObservability/OpenMetrics/blob/main/specification/OpenMetrics.md)
+/// with [`PrometheusRecorder::route`], or added to your own [`Registry`] with
+/// [`PrometheusRecorder::register`].
+///
+/// # Example
+/// ```rust
+/// use std::sync::Arc;
+///
+/// use rocket_cors::metrics::PrometheusRecorder;
+/// use rocket_cors::CorsOptions;
+///
+/// let recorder = Arc::new(PrometheusRecorder::new());
+/// let cors = CorsOptions::default()
+///     .metrics(recorder.clone())
+///     .to_cors()
+///     .expect("To not fail");
+///
+/// let _ = rocket::build()
+///     .attach(cors)
+///     .mount("/", vec![recorder.route("/metrics")]);
+/// ```
+#[derive(Debug)]
+pub struct PrometheusRecorder {
+    requests: Family<Labels, Counter>,
+    denied: Family<DeniedLabels, Counter>,
+    preflight_duration: Histogram,
+    origin_cache_lookups: Family<[(&'static str, &'static str); 1], Counter>,
+    origin_cache_size: Gauge,
+    registry: Registry,
+}
+
+impl PrometheusRecorder {
+    /// Create a recorder with all the metrics at zero
+    pub fn new() -> Self {
+        let requests = Family::default();
+        let denied = Family::default();
+        // 1 microsecond to about 33 milliseconds
+        let preflight_duration = Histogram::new(exponential_buckets(0.000_001, 2.0, 16));
+
+        let mut recorder = Self {
+            requests,
+            denied,
+            preflight_duration,
+            origin_cache_lookups: Family::default(),
+            origin_cache_size: Gauge::default(),
+            registry: Registry::default(),
+        };
+        let mut registry = Registry::default();
+        recorder.register(&mut registry);
+        recorder.registry = registry;
+        recorder
+    }
+
+    /// Register the metrics with a [`Registry`], to expose them together with your own metrics
+    ///
+    /// The metrics are shared, so they can be registered with more than one registry.
+    pub fn register(&self, registry: &mut Registry) {
+        let registry = registry.sub_registry_with_prefix("rocket_cors");
+        registry.register(
+            "requests",
+            "CORS requests by kind and outcome",
+            self.requests.clone(),
+        );
+        registry.register(
+            "denied",
+            "Denied CORS requests by kind and reason",
+            self.denied.clone(),
+        );
+        registry.register(
+            "preflight_duration_seconds",
+            "Time taken to validate pre-flight requests",
+            self.preflight_duration.clone(),
+        );
+        registry.register(
+            "origin_cache_lookups",
+            "Lookups in the origin cache by result",
+            self.origin_cache_lookups.clone(),
+        );
+        registry.register(
+            "origin_cache_size",
+            "Number of origins in the origin cache",
+            self.origin_cache_size.clone(),
+        );
+    }
+
+    /// Encode the metrics in the
+    /// [OpenMetrics text format](https://github.com/OpenObservability/OpenMetrics/blob/main/specification/OpenMetrics.md)
+    pub fn encode(&self) -> String {
+        let mut encoded = String::new();
+        text::encode(&mut encoded, &self.registry).expect("writing to a String not to fail");
+        encoded
+    }
+
+    /// Returns a `GET` route at `path` serving the metrics in the
+    /// [OpenMetrics text format](https://github.com/OpenObservability/OpenMetrics/blob/main/specification/OpenMetrics.md)
+    pub fn route(self: &Arc<Self>, path: &str) -> Route {
+        Route::new(Method::Get, path, MetricsRoute(Arc::clone(self)))
+    }
+}
+
+impl Default for PrometheusRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CorsMetrics for PrometheusRecorder {
+    fn preflight(&self, elapsed: Duration) {
+        self.preflight_duration.observe(elapsed.as_secs_f64());
+    }
+
+    fn allowed(&self, kind: RequestKind) {
+        let _ = self
+            .requests
+            .get_or_create(&[("kind", kind_label(kind)), ("outcome", "allowed")])
+            .inc();
+    }
+
+    fn denied(&self, kind: RequestKind, error: &Error) {
+        let _ = self
+            .requests
+            .get_or_create(&[("kind", kind_label(kind)), ("outcome", "denied")])
+            .inc();
+        let _ = self
+            .denied
+            .get_or_create(&[
+                ("kind", kind_label(kind).to_string()),
+                ("reason", reason_label(error)),
+            ])
+            .inc();
+    }
+
+    fn origin_cache(&self, hit: bool, size: usize) {
+        let result = if hit { "hit" } else { "miss" };
+        let _ = self
+            .origin_cache_lookups
+            .get_or_create(&[("result", result)])
+            .inc();
+        let _ = self
+            .origin_cache_size
+            .set(i64::try_from(size).unwrap_or(i64::MAX));
+    }
+}
+
+/// Handler serving the encoded metrics of a [`PrometheusRecorder`]
+#[derive(Clone)]
+struct MetricsRoute(Arc<PrometheusRecorder>);
+
+#[rocket::async_trait]
+impl route::Handler for MetricsRoute {
+    async fn handle<'r>(&self, request: &'r Request<'_>, _: Data<'r>) -> route::Outcome<'r> {
+        let content_type = ContentType::new("application", "openmetrics-text")
+            .with_params([("version", "1.0.0"), ("charset", "utf-8")]);
+        route::Outcome::from(request, (content_type, self.0.encode()))
+    }
+}
+
+fn kind_label(kind: RequestKind) -> &'static str {
+    match kind {
+        RequestKind::Preflight => "preflight",
+        RequestKind::Actual => "actual",
+    }
+}
+
+fn reason_label(error: &Error) -> String {
+    error.code().replace('-', "_")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use rocket::http::Status;
+    use rocket::local::blocking::Client;
+
+    use super::*;
+
+    #[test]
+    fn decisions_are_encoded() {
+        let recorder = PrometheusRecorder::new();
+        recorder.preflight(Duration::from_micros(3));
+        recorder.allowed(RequestKind::Preflight);
+        recorder.allowed(RequestKind::Actual);
+        recorder.allowed(RequestKind::Actual);
+        recorder.denied(
+            RequestKind::Actual,
+            &Error::OriginNotAllowed("https://www.example.com".to_string()),
+        );
+        recorder.origin_cache(false, 1);
+        recorder.origin_cache(true, 1);
+        recorder.origin_cache(true, 1);
+
+        let encoded = recorder.encode();
+        for line in [
+            r#"rocket_cors_requests_total{kind="preflight",outcome="allowed"} 1"#,
+            r#"rocket_cors_requests_total{kind="actual",outcome="allowed"} 2"#,
+            r#"rocket_cors_requests_total{kind="actual",outcome="denied"} 1"#,
+            r#"rocket_cors_denied_total{kind="actual",reason="origin_not_allowed"} 1"#,
+            "rocket_cors_preflight_duration_seconds_count 1",
+            r#"rocket_cors_origin_cache_lookups_total{result="hit"} 2"#,
+            r#"rocket_cors_origin_cache_lookups_total{result="miss"} 1"#,
+            "rocket_cors_origin_cache_size 1",
+        ] {
+            assert!(encoded.contains(line), "{} not in {}", line, encoded);
+        }
+    }
+
+    #[test]
+    fn route_serves_encoded_metrics() {
+        let recorder = Arc::new(PrometheusRecorder::new());
+        recorder.allowed(RequestKind::Actual);
+
+        let rocket = rocket::build().mount("/", vec![recorder.route("/metrics")]);
+        let client = Client::tracked(rocket).expect("to not fail");
+        let response = client.get("/metrics").dispatch();
+
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.headers().get_one("Content-Type"),
+            Some("application/openmetrics-text; version=1.0.0; charset=utf-8")
+        );
+        assert_eq!(response.into_string(), Some(recorder.encode()));
+    }
+}
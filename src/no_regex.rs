@@ -0,0 +1,29 @@
+//! Stand-ins for the types of the `regex` crate when the `regex` feature is disabled
+//!
+//! Regex origins cannot be compiled without the feature, so a [`RegexSet`] can never be built.
+
+/// Stand-in for `regex::RegexSet`, which cannot be constructed
+#[derive(Clone, Debug)]
+pub(crate) enum RegexSet {}
+
+impl RegexSet {
+    pub(crate) fn is_match(&self, _: &str) -> bool {
+        match *self {}
+    }
+
+    pub(crate) fn patterns(&self) -> &[String] {
+        match *self {}
+    }
+}
+
+/// Escapes the regex meta characters in `text`, like `regex::escape`
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\.+*?()|[]{}^$#&-~".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
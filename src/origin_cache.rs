@@ -0,0 +1,134 @@
+//! A bounded LRU cache of the origins matched against the regex origins, enabled with
+//! [`CorsOptions::origin_cache_size`](crate::CorsOptions::origin_cache_size)
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::metrics::CorsMetrics;
+use crate::Hook;
+
+/// Whether origins matched the regex origins, keyed by their ASCII serialization
+///
+/// The least recently used origin is evicted when the cache is full. Lookups are reported to
+/// [`CorsMetrics::origin_cache`].
+pub(crate) struct OriginCache {
+    capacity: usize,
+    metrics: Hook<dyn CorsMetrics>,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Default)]
+struct Entries {
+    /// Whether each origin matched, and when it was last used
+    matches: HashMap<String, (bool, u64)>,
+    /// The origins by when they were last used
+    recency: BTreeMap<u64, String>,
+    /// Incremented every time an origin is used
+    clock: u64,
+}
+
+impl Entries {
+    /// Returns whether `origin` matched, marking it as the most recently used
+    fn get(&mut self, origin: &str) -> Option<bool> {
+        self.clock += 1;
+        let clock = self.clock;
+        let (matched, used) = self.matches.get_mut(origin)?;
+        let origin = self.recency.remove(used).expect("to be in the recency");
+        *used = clock;
+        let _ = self.recency.insert(clock, origin);
+        Some(*matched)
+    }
+
+    /// Inserts `origin` as the most recently used, evicting the least recently used origin if the
+    /// cache is full
+    fn insert(&mut self, origin: &str, matched: bool, capacity: usize) {
+        if self.matches.contains_key(origin) {
+            // Inserted by another request since we looked
+            return;
+        }
+        if self.matches.len() >= capacity {
+            if let Some((_, evicted)) = self.recency.pop_first() {
+                let _ = self.matches.remove(&evicted);
+            }
+        }
+        self.clock += 1;
+        let _ = self
+            .matches
+            .insert(origin.to_string(), (matched, self.clock));
+        let _ = self.recency.insert(self.clock, origin.to_string());
+    }
+}
+
+impl OriginCache {
+    /// Create an empty cache of up to `capacity` origins, which reports lookups to `metrics`
+    pub(crate) fn new(capacity: usize, metrics: Hook<dyn CorsMetrics>) -> Self {
+        Self {
+            capacity,
+            metrics,
+            entries: Mutex::default(),
+        }
+    }
+
+    /// Returns whether `origin` matched if it is cached, and otherwise calls `is_match` and
+    /// caches its answer
+    ///
+    /// The cache is not locked while `is_match` is called.
+    pub(crate) fn get_or_insert_with<F>(&self, origin: &str, is_match: F) -> bool
+    where
+        F: FnOnce() -> bool,
+    {
+        let cached = self.lock().get(origin);
+        let (matched, hit) = match cached {
+            Some(matched) => (matched, true),
+            None => {
+                let matched = is_match();
+                self.lock().insert(origin, matched, self.capacity);
+                (matched, false)
+            }
+        };
+        debug_!("Origin cache hit? {}", hit);
+        if let Some(metrics) = self.metrics.get() {
+            metrics.origin_cache(hit, self.len());
+        }
+        matched
+    }
+
+    /// Returns the number of origins in the cache
+    pub(crate) fn len(&self) -> usize {
+        self.lock().matches.len()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for OriginCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OriginCache")
+            .field("capacity", &self.capacity)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn least_recently_used_origins_are_evicted() {
+        let cache = OriginCache::new(2, Hook::none());
+        assert!(cache.get_or_insert_with("https://a.acme.com", || true));
+        assert!(!cache.get_or_insert_with("https://b.acme.com", || false));
+        // Cached, so the answer is not computed again
+        assert!(cache.get_or_insert_with("https://a.acme.com", || false));
+
+        assert!(cache.get_or_insert_with("https://c.acme.com", || true));
+        assert_eq!(cache.len(), 2);
+        // `b` was the least recently used
+        assert!(cache.get_or_insert_with("https://b.acme.com", || true));
+        assert!(cache.get_or_insert_with("https://c.acme.com", || false));
+    }
+}
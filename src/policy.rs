@@ -0,0 +1,290 @@
+//! Policies that validate CORS requests
+//!
+//! [`Cors`] is the policy built from [`CorsOptions`](crate::CorsOptions), but the [`Guard`], the
+//! [`ManualResponder`] and the [`PolicyFairing`] only depend on the [`CorsPolicy`] trait. Implement
+//! it to validate requests some other way, such as with a mock policy in tests, or combine
+//! policies with a [`PolicyChain`].
+//!
+//! The `Guard` and the catch all `OPTIONS` routes use an `Arc<dyn CorsPolicy>` in Rocket's managed
+//! state when there is no `Cors` in it.
+//!
+//! # Example
+//! ```rust
+//! use std::sync::Arc;
+//!
+//! use rocket_cors::policy::{CorsPolicy, PolicyChain, PolicyFairing};
+//! use rocket_cors::CorsOptions;
+//!
+//! let strict = CorsOptions::strict(&["https://www.acme.com"])
+//!     .to_cors()
+//!     .expect("To not fail");
+//! let partners = CorsOptions::strict(&["https://www.partner.com"])
+//!     .to_cors()
+//!     .expect("To not fail");
+//! let policy: Arc<dyn CorsPolicy> = Arc::new(PolicyChain::new(strict).or(partners));
+//!
+//! let _ = rocket::build()
+//!     .attach(PolicyFairing::from(Arc::clone(&policy)))
+//!     .manage(policy);
+//! ```
+//!
+//! [`Guard`]: crate::Guard
+//! [`ManualResponder`]: crate::ManualResponder
+
+use std::sync::Arc;
+
+use rocket::{Data, Request, Response};
+
+use crate::fairing::{on_policy_request, on_response_wrapper};
+use crate::{
+    error_response, lookup_origin_store, validate_and_build, Cors, CorsHeaders, Error,
+    UnmatchedOptions,
+};
+
+/// Validates CORS requests and builds the CORS headers of their responses
+#[rocket::async_trait]
+pub trait CorsPolicy: Send + Sync {
+    /// Does the asynchronous work that [`CorsPolicy::validate`] needs for `request`, such as
+    /// looking up its origin in an origin store, and keeps the result in the request-local cache
+    ///
+    /// This is called before `validate` by the [`Guard`](crate::Guard) and the fairings, but not
+    /// by a [`ManualResponder`](crate::ManualResponder), which responds synchronously. Does
+    /// nothing by default.
+    async fn prepare(&self, request: &Request<'_>) {
+        let _ = request;
+    }
+
+    /// Validates `request` and returns the CORS headers of its response
+    ///
+    /// Requests that are not CORS requests should be allowed, usually with empty headers.
+    fn validate(&self, request: &Request<'_>) -> Result<CorsHeaders, Error>;
+
+    /// Returns the CORS headers of the error response to a request that failed validation
+    ///
+    /// By default, the response only varies on `Origin`.
+    fn error_headers(&self, request: &Request<'_>) -> CorsHeaders {
+        let _ = request;
+        CorsHeaders::new().vary_origin()
+    }
+
+    /// Returns what the fairings respond to an allowed pre-flight request that no route matches
+    fn unmatched_options(&self) -> UnmatchedOptions {
+        UnmatchedOptions::default()
+    }
+}
+
+#[rocket::async_trait]
+impl CorsPolicy for Cors {
+    async fn prepare(&self, request: &Request<'_>) {
+        lookup_origin_store(self, request).await;
+    }
+
+    fn validate(&self, request: &Request<'_>) -> Result<CorsHeaders, Error> {
+        validate_and_build(self, request)
+    }
+
+    fn error_headers(&self, request: &Request<'_>) -> CorsHeaders {
+        error_response(self, request)
+    }
+
+    fn unmatched_options(&self) -> UnmatchedOptions {
+        self.unmatched_options
+    }
+}
+
+#[rocket::async_trait]
+impl<P: CorsPolicy + ?Sized> CorsPolicy for Arc<P> {
+    async fn prepare(&self, request: &Request<'_>) {
+        (**self).prepare(request).await;
+    }
+
+    fn validate(&self, request: &Request<'_>) -> Result<CorsHeaders, Error> {
+        (**self).validate(request)
+    }
+
+    fn error_headers(&self, request: &Request<'_>) -> CorsHeaders {
+        (**self).error_headers(request)
+    }
+
+    fn unmatched_options(&self) -> UnmatchedOptions {
+        (**self).unmatched_options()
+    }
+}
+
+/// A policy that validates requests with each of its policies in turn, until one of them allows
+/// the request
+///
+/// If none does, the error of the last policy is returned. The error headers and the response to
+/// unmatched pre-flight requests are those of the first policy. Each policy reports the requests
+/// that it validates to its own metrics, so a request allowed by a fallback policy is also
+/// reported as denied by the policies before it.
+pub struct PolicyChain {
+    policies: Vec<Arc<dyn CorsPolicy>>,
+}
+
+impl PolicyChain {
+    /// Create a chain that validates requests with `first`
+    pub fn new<P: CorsPolicy + 'static>(first: P) -> Self {
+        PolicyChain {
+            policies: vec![Arc::new(first)],
+        }
+    }
+
+    /// Adds `policy` to the end of the chain, to validate the requests that the policies before
+    /// it deny
+    #[must_use]
+    pub fn or<P: CorsPolicy + 'static>(mut self, policy: P) -> Self {
+        self.policies.push(Arc::new(policy));
+        self
+    }
+}
+
+#[rocket::async_trait]
+impl CorsPolicy for PolicyChain {
+    async fn prepare(&self, request: &Request<'_>) {
+        for policy in &self.policies {
+            policy.prepare(request).await;
+        }
+    }
+
+    fn validate(&self, request: &Request<'_>) -> Result<CorsHeaders, Error> {
+        let (last, policies) = self.policies.split_last().expect("to have a policy");
+        policies
+            .iter()
+            .find_map(|policy| policy.validate(request).ok())
+            .map_or_else(|| last.validate(request), Ok)
+    }
+
+    fn error_headers(&self, request: &Request<'_>) -> CorsHeaders {
+        self.policies[0].error_headers(request)
+    }
+
+    fn unmatched_options(&self) -> UnmatchedOptions {
+        self.policies[0].unmatched_options()
+    }
+}
+
+/// A [Fairing](https://rocket.rs/guide/fairings/) that validates every request with a
+/// [`CorsPolicy`]
+///
+/// Requests that fail validation are not routed, and get an error response like with the `Cors`
+/// fairing. The fairing options of a `Cors`, such as
+/// [`fairing_scope`](crate::CorsOptions::fairing_scope), only apply to the `Cors` fairing, so
+/// attach a `Cors` directly to use them.
+pub struct PolicyFairing {
+    policy: Arc<dyn CorsPolicy>,
+}
+
+impl PolicyFairing {
+    /// Create a fairing that validates requests with `policy`
+    pub fn new<P: CorsPolicy + 'static>(policy: P) -> Self {
+        PolicyFairing {
+            policy: Arc::new(policy),
+        }
+    }
+}
+
+impl From<Arc<dyn CorsPolicy>> for PolicyFairing {
+    fn from(policy: Arc<dyn CorsPolicy>) -> Self {
+        PolicyFairing { policy }
+    }
+}
+
+#[rocket::async_trait]
+impl rocket::fairing::Fairing for PolicyFairing {
+    fn info(&self) -> rocket::fairing::Info {
+        rocket::fairing::Info {
+            name: "CORS Policy",
+            kind: rocket::fairing::Kind::Request | rocket::fairing::Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        on_policy_request(&*self.policy, request).await;
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        on_response_wrapper(request, response).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocket::http::{Header, Status};
+    use rocket::local::blocking::Client;
+
+    use super::*;
+    use crate::{AllowedOrigins, CorsOptions, Guard, Responder};
+
+    /// A policy that denies every request
+    struct DenyAll;
+
+    impl CorsPolicy for DenyAll {
+        fn validate(&self, request: &Request<'_>) -> Result<CorsHeaders, Error> {
+            let origin = request.headers().get_one("Origin").unwrap_or_default();
+            Err(Error::OriginNotAllowed(origin.to_string()))
+        }
+    }
+
+    #[rocket::get("/")]
+    fn index(cors: Guard<'_>) -> Responder<&'static str> {
+        cors.responder("Hello CORS")
+    }
+
+    fn allow(origin: &str) -> Cors {
+        CorsOptions::default()
+            .allowed_origins(AllowedOrigins::some_exact(&[origin]))
+            .to_cors()
+            .expect("To not fail")
+    }
+
+    #[test]
+    fn guard_uses_the_managed_policy() {
+        let policy: Arc<dyn CorsPolicy> = Arc::new(DenyAll);
+        let rocket = rocket::build()
+            .mount("/", rocket::routes![index])
+            .manage(policy);
+        let client = Client::tracked(rocket).expect("valid rocket instance");
+
+        let response = client
+            .get("/")
+            .header(Header::new("Origin", "https://www.acme.com"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+    }
+
+    #[test]
+    fn chained_policies_fall_back_in_order() {
+        let chain = PolicyChain::new(DenyAll)
+            .or(allow("https://www.acme.com"))
+            .or(allow("https://www.partner.com"));
+        let rocket = rocket::build()
+            .mount("/", rocket::routes![index])
+            .attach(PolicyFairing::new(chain))
+            .manage(allow("https://www.partner.com"));
+        let client = Client::tracked(rocket).expect("valid rocket instance");
+
+        let response = client
+            .get("/")
+            .header(Header::new("Origin", "https://www.partner.com"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            Some("https://www.partner.com")
+        );
+
+        // Allowed by the chain, but not by the guard
+        let response = client
+            .get("/")
+            .header(Header::new("Origin", "https://www.acme.com"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+
+        let response = client
+            .get("/")
+            .header(Header::new("Origin", "https://www.evil.com"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+    }
+}
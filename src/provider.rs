@@ -0,0 +1,169 @@
+//! [Figment](https://docs.rs/figment) configuration, enabled with the `figment` feature
+
+use rocket::figment::providers::{Env, Serialized};
+use rocket::figment::value::{Dict, Map};
+use rocket::figment::{Error, Figment, Metadata, Profile, Provider};
+
+use crate::CorsOptions;
+
+impl CorsOptions {
+    /// The key of the options in a [`Figment`]
+    pub const FIGMENT_KEY: &'static str = "cors";
+
+    /// Extract options from the `cors` key of a [`Figment`], such as the one Rocket is configured
+    /// with
+    ///
+    /// Each option can be overridden with an environment variable named `ROCKET_CORS_`, followed
+    /// by the name of the option in upper case, such as `ROCKET_CORS_MAX_AGE=3600` or
+    /// `ROCKET_CORS_ALLOWED_ORIGINS=["https://www.acme.com"]`. Options that are not configured keep
+    /// their default value.
+    ///
+    /// Options that are skipped when (de)serializing, such as [`CorsOptions::metrics`], are never
+    /// configured.
+    ///
+    /// The options are read for the profile selected in the figment. Rocket selects the `debug`
+    /// profile in debug builds and the `release` profile in release builds, unless `ROCKET_PROFILE`
+    /// is set. Options of the `debug` profile are [`CorsOptions::development_only`], so that
+    /// permissive settings in `[debug.cors]` cannot end up in a release build: there,
+    /// [`CorsOptions::to_cors`] fails with [`Error::DevelopmentOnly`](crate::Error::DevelopmentOnly).
+    /// Set `development_only = false` in `[debug.cors]` to use them in release builds anyway.
+    ///
+    /// # Example
+    ///
+    /// With options for each profile in `Rocket.toml`:
+    ///
+    /// ```toml
+    /// [default.cors]
+    /// allowed_origins = ["https://www.acme.com"]
+    /// allow_credentials = true
+    ///
+    /// [debug.cors]
+    /// allowed_origins = ["https://www.acme.com", "http://localhost:8080"]
+    /// ```
+    ///
+    /// ```rust,no_run
+    /// use rocket_cors::CorsOptions;
+    ///
+    /// # fn rocket() -> rocket::Rocket<rocket::Build> {
+    /// let rocket = rocket::build();
+    /// let cors = CorsOptions::from_figment(rocket.figment())
+    ///     .expect("valid CORS options")
+    ///     .to_cors()
+    ///     .expect("To not fail");
+    ///
+    /// rocket.attach(cors)
+    /// # }
+    /// # let _ = rocket;
+    /// ```
+    // The same error as `Figment::extract`
+    #[allow(clippy::result_large_err)]
+    pub fn from_figment(figment: &Figment) -> Result<Self, Error> {
+        let env = Env::prefixed("ROCKET_CORS_")
+            .map(|key| format!("{}.{}", Self::FIGMENT_KEY, key).into())
+            .global();
+
+        // Only joined, so that an explicit `development_only` takes precedence
+        let debug_only = Serialized::from(true, rocket::Config::DEBUG_PROFILE)
+            .key(&format!("{}.development_only", Self::FIGMENT_KEY));
+
+        figment
+            .clone()
+            .join(Self::default())
+            .join(debug_only)
+            .merge(env)
+            .extract_inner(Self::FIGMENT_KEY)
+    }
+}
+
+/// Provides the options as defaults for the `cors` key of every profile, so that they can be
+/// overridden by Rocket's configuration
+impl Provider for CorsOptions {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("CORS Options")
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        Serialized::defaults(self).key(Self::FIGMENT_KEY).data()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocket::figment::providers::{Format, Toml};
+
+    use super::*;
+    use crate::AllowedOrigins;
+
+    const TOML: &str = r#"
+[default.cors]
+allowed_origins = ["https://www.acme.com"]
+max_age = 42
+
+[debug.cors]
+allowed_origins = "*"
+"#;
+
+    #[test]
+    fn options_are_extracted_for_the_profile() {
+        let figment = Figment::from(Toml::string(TOML).nested());
+
+        let options = not_err!(CorsOptions::from_figment(
+            &figment.clone().select("release")
+        ));
+        assert_eq!(
+            options.allowed_origins,
+            AllowedOrigins::some_exact(&["https://www.acme.com"])
+        );
+        assert_eq!(options.max_age, Some(42));
+        assert_eq!(
+            options.allowed_methods,
+            CorsOptions::default().allowed_methods
+        );
+
+        let options = not_err!(CorsOptions::from_figment(&figment.select("debug")));
+        assert_eq!(options.allowed_origins, AllowedOrigins::all());
+        assert_eq!(options.max_age, Some(42));
+    }
+
+    #[test]
+    fn max_age_can_be_a_duration() {
+        let toml = "[default.cors]\nmax_age = \"1h 30m\"\n";
+        let figment = Figment::from(Toml::string(toml).nested());
+        let options = not_err!(CorsOptions::from_figment(&figment));
+        assert_eq!(options.max_age, Some(5400));
+    }
+
+    #[test]
+    fn debug_options_are_development_only() {
+        let figment = Figment::from(Toml::string(TOML).nested());
+
+        let options = not_err!(CorsOptions::from_figment(&figment.clone().select("debug")));
+        assert!(options.development_only);
+        let options = not_err!(CorsOptions::from_figment(
+            &figment.clone().select("release")
+        ));
+        assert!(!options.development_only);
+
+        let toml = format!("{}development_only = false\n", TOML);
+        let figment = Figment::from(Toml::string(&toml).nested()).select("debug");
+        let options = not_err!(CorsOptions::from_figment(&figment));
+        assert!(!options.development_only);
+        assert_eq!(options.allowed_origins, AllowedOrigins::all());
+    }
+
+    #[test]
+    fn options_are_defaults_for_the_figment() {
+        let defaults = CorsOptions::default()
+            .max_age(Some(42))
+            .allow_credentials(true);
+        let figment = Figment::from(defaults).merge(Toml::string(TOML).nested());
+
+        let options = not_err!(CorsOptions::from_figment(&figment.select("debug")));
+        assert_eq!(options.allowed_origins, AllowedOrigins::all());
+        assert_eq!(options.max_age, Some(42));
+        assert!(options.allow_credentials);
+
+        let options = not_err!(CorsOptions::from_figment(&Figment::new()));
+        assert_eq!(options, CorsOptions::default());
+    }
+}
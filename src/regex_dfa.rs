@@ -0,0 +1,71 @@
+//! Origin matching with a dense DFA from `regex-automata`, enabled with the `regex-dfa` feature
+
+use std::fmt;
+
+use regex_automata::dfa::{dense, Automaton};
+use regex_automata::Input;
+
+use crate::Error;
+
+/// Origin regexes compiled into a single dense DFA
+///
+/// Unlike a `RegexSet`, which determinizes lazily while matching, the whole DFA is built when
+/// the `Cors` is created, so that matching takes a predictable time.
+#[derive(Clone)]
+pub(crate) struct DenseRegexSet {
+    dfa: dense::DFA<Vec<u32>>,
+    patterns: Vec<String>,
+}
+
+impl DenseRegexSet {
+    /// Compile `patterns`, with the DFA limited to `size_limit` bytes, and the memory used to
+    /// build it limited to `determinize_size_limit` bytes
+    pub(crate) fn new(
+        patterns: Vec<String>,
+        size_limit: Option<usize>,
+        determinize_size_limit: Option<usize>,
+    ) -> Result<Self, Error> {
+        let mut config = dense::Config::new();
+        if size_limit.is_some() {
+            config = config.dfa_size_limit(size_limit);
+        }
+        if determinize_size_limit.is_some() {
+            config = config.determinize_size_limit(determinize_size_limit);
+        }
+
+        let dfa = dense::Builder::new()
+            .configure(config)
+            .build_many(&patterns)
+            .map_err(|error| match size_limit.or(determinize_size_limit) {
+                Some(limit) if error.is_size_limit_exceeded() => Error::RegexTooBig(limit),
+                _ => Error::RegexDfaError(Box::new(error)),
+            })?;
+        Ok(DenseRegexSet { dfa, patterns })
+    }
+
+    /// Returns whether any of the patterns matches anywhere in `haystack`
+    pub(crate) fn is_match(&self, haystack: &str) -> bool {
+        let input = Input::new(haystack).earliest(true);
+        match self.dfa.try_search_fwd(&input) {
+            Ok(half_match) => half_match.is_some(),
+            Err(error) => {
+                warn_!("Failed to match against the regex origins: {}", error);
+                false
+            }
+        }
+    }
+
+    /// Returns the patterns that the DFA was compiled from
+    pub(crate) fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+}
+
+impl fmt::Debug for DenseRegexSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DenseRegexSet")
+            .field("patterns", &self.patterns)
+            .field("memory_usage", &self.dfa.memory_usage())
+            .finish()
+    }
+}
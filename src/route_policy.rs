@@ -0,0 +1,244 @@
+//! CORS policies that depend on the route that handles the request
+//!
+//! Endpoints that need a different policy than the rest of the application, such as a public
+//! endpoint that allows all origins, can be given their own [`Cors`] by the name of their route.
+//! Attach a [`RoutePolicies`] instead of the `Cors` fairing, so that the handlers do not have to
+//! be rewritten to use the guard or the manual mode.
+//!
+//! # Example
+//! ```rust
+//! use rocket::get;
+//! use rocket_cors::route_policy::RoutePolicies;
+//! use rocket_cors::{AllowedOrigins, CorsOptions};
+//!
+//! #[get("/users")]
+//! fn list_users() -> &'static str {
+//!     "[]"
+//! }
+//!
+//! #[get("/status")]
+//! fn status() -> &'static str {
+//!     "OK"
+//! }
+//!
+//! # fn main() {
+//! let default = CorsOptions::strict(&["https://www.acme.com"])
+//!     .to_cors()
+//!     .expect("To not fail");
+//! let public = CorsOptions::default()
+//!     .allowed_origins(AllowedOrigins::all())
+//!     .to_cors()
+//!     .expect("To not fail");
+//!
+//! let _ = rocket::build()
+//!     .mount("/", rocket::routes![list_users, status])
+//!     .attach(RoutePolicies::new(default).route("status", public));
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use rocket::http::Method;
+use rocket::{Data, Request, Response};
+
+use crate::fairing::{on_request_wrapper, on_response_wrapper, route_matches_method};
+use crate::{is_preflight, Cors};
+
+/// A [Fairing](https://rocket.rs/guide/fairings/) that validates each request with the policy
+/// bound to the name of the route that handles it
+///
+/// Fairings run before Rocket routes a request, so the route is found the same way as for
+/// [`CorsOptions::fairing_exempt_routes`](crate::CorsOptions::fairing_exempt_routes): by the
+/// method and path of the request. Pre-flight requests use the policy of the route for the method
+/// in their `Access-Control-Request-Method` header. If more than one route matches, the one with
+/// the lowest rank is used.
+///
+/// Requests that no route with a policy matches are validated with the default policy.
+///
+/// Attach this fairing instead of the `Cors` fairing, not in addition to it.
+pub struct RoutePolicies {
+    default: Arc<Cors>,
+    routes: HashMap<String, Arc<Cors>>,
+}
+
+impl RoutePolicies {
+    /// Create a fairing that validates requests with `default`, until policies are bound to
+    /// routes with [`RoutePolicies::route`]
+    pub fn new(default: Cors) -> Self {
+        RoutePolicies {
+            default: Arc::new(default),
+            routes: HashMap::new(),
+        }
+    }
+
+    /// Binds `policy` to the route named `name`, which is the name of the handler function for
+    /// routes declared with Rocket's attributes
+    #[must_use]
+    pub fn route<S: Into<String>>(mut self, name: S, policy: Cors) -> Self {
+        let _ = self.routes.insert(name.into(), Arc::new(policy));
+        self
+    }
+
+    /// Returns the policy to validate `request` with
+    fn policy(&self, request: &Request<'_>) -> &Arc<Cors> {
+        let method = if is_preflight(request) {
+            request
+                .headers()
+                .get_one("Access-Control-Request-Method")
+                .and_then(|method| method.trim().parse::<Method>().ok())
+        } else {
+            Some(request.method())
+        };
+        let route = method.and_then(|method| {
+            request
+                .rocket()
+                .routes()
+                .filter(|route| route_matches_method(route, method, request))
+                .min_by_key(|route| route.rank)
+        });
+
+        let name = route.and_then(|route| route.name.as_deref());
+        match name.and_then(|name| Some((name, self.routes.get(name)?))) {
+            Some((name, policy)) => {
+                debug_!("CORS Route Policies: Using the policy of route {}", name);
+                policy
+            }
+            None => &self.default,
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl rocket::fairing::Fairing for RoutePolicies {
+    fn info(&self) -> rocket::fairing::Info {
+        rocket::fairing::Info {
+            name: "CORS Route Policies",
+            kind: rocket::fairing::Kind::Ignite
+                | rocket::fairing::Kind::Liftoff
+                | rocket::fairing::Kind::Request
+                | rocket::fairing::Kind::Response,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: rocket::Rocket<rocket::Build>) -> rocket::fairing::Result {
+        let mut unknown: Vec<&str> = self
+            .routes
+            .keys()
+            .map(String::as_str)
+            .filter(|name| {
+                !rocket
+                    .routes()
+                    .any(|route| route.name.as_deref() == Some(*name))
+            })
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort_unstable();
+            warn_!(
+                "CORS Route Policies: No route is named {}. Their policies are never used.",
+                unknown.join(", ")
+            );
+        }
+
+        rocket::fairing::Fairing::on_ignite(&*self.default, rocket).await
+    }
+
+    async fn on_liftoff(&self, rocket: &rocket::Rocket<rocket::Orbit>) {
+        rocket::fairing::Fairing::on_liftoff(&*self.default, rocket).await
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        let policy = Arc::clone(self.policy(request));
+        on_request_wrapper(&policy, request).await;
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        on_response_wrapper(request, response).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocket::http::{Header, Status};
+    use rocket::local::blocking::Client;
+
+    use super::*;
+    use crate::{AllowedOrigins, CorsOptions};
+
+    #[rocket::get("/users")]
+    fn list_users() -> &'static str {
+        "Users"
+    }
+
+    #[rocket::get("/")]
+    fn index() -> &'static str {
+        "Hello CORS"
+    }
+
+    fn client() -> Client {
+        let policy = |origin: &str| {
+            CorsOptions::default()
+                .allowed_origins(AllowedOrigins::some_exact(&[origin]))
+                .to_cors()
+                .expect("To not fail")
+        };
+        let policies = RoutePolicies::new(policy("https://www.acme.com"))
+            .route("list_users", policy("https://www.users.com"));
+        let rocket = rocket::build()
+            .mount("/", rocket::routes![index, list_users])
+            .attach(policies);
+        Client::tracked(rocket).expect("valid rocket instance")
+    }
+
+    #[test]
+    fn requests_are_validated_with_the_policy_of_their_route() {
+        let client = client();
+
+        for (path, origin, allowed) in [
+            ("/users", "https://www.users.com", true),
+            ("/users", "https://www.acme.com", false),
+            ("/", "https://www.acme.com", true),
+            ("/", "https://www.users.com", false),
+            ("/missing", "https://www.acme.com", true),
+        ] {
+            let response = client
+                .get(path)
+                .header(Header::new("Origin", origin))
+                .dispatch();
+            if allowed {
+                assert_eq!(
+                    response.headers().get_one("Access-Control-Allow-Origin"),
+                    Some(origin),
+                    "{} {}",
+                    path,
+                    origin
+                );
+            } else {
+                assert_eq!(response.status(), Status::Forbidden, "{} {}", path, origin);
+            }
+        }
+    }
+
+    #[test]
+    fn preflight_requests_use_the_policy_of_the_requested_method() {
+        let client = client();
+
+        let response = client
+            .options("/users")
+            .header(Header::new("Origin", "https://www.users.com"))
+            .header(Header::new("Access-Control-Request-Method", "GET"))
+            .dispatch();
+        assert_eq!(response.status(), Status::NoContent);
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            Some("https://www.users.com")
+        );
+
+        let response = client
+            .options("/users")
+            .header(Header::new("Origin", "https://www.users.com"))
+            .header(Header::new("Access-Control-Request-Method", "POST"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+    }
+}
@@ -0,0 +1,196 @@
+//! [JSON Schema](https://json-schema.org/) of the options, enabled with the `schemars` feature
+//!
+//! Most types derive `JsonSchema`. The ones that are deserialized from more than one form, or
+//! with a custom implementation, describe their forms by hand here.
+
+use std::collections::HashSet;
+
+use log::LevelFilter;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Schema, SchemaObject};
+use schemars::JsonSchema;
+use serde_derive::Serialize;
+
+use crate::headers::HeaderFieldName;
+use crate::{AllOrSome, Method, Origins};
+
+/// A string schema, only allowing `values` if any are given
+fn string_schema(values: &[&str]) -> Schema {
+    let mut schema = SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        ..Default::default()
+    };
+    if !values.is_empty() {
+        schema.enum_values = Some(values.iter().map(|value| (*value).into()).collect());
+    }
+    schema.into()
+}
+
+/// A schema allowing any of `schemas`
+fn any_of(schemas: Vec<Schema>) -> Schema {
+    let mut schema = SchemaObject::default();
+    schema.subschemas().any_of = Some(schemas);
+    schema.into()
+}
+
+impl<T: JsonSchema> JsonSchema for AllOrSome<T> {
+    fn schema_name() -> String {
+        format!("AllOrSome_for_{}", T::schema_name())
+    }
+
+    /// `"*"`, `"All"`, `{"Some": ...}`, or the allowed values on their own
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let mut tagged = SchemaObject {
+            instance_type: Some(InstanceType::Object.into()),
+            ..Default::default()
+        };
+        let object = tagged.object();
+        let _ = object
+            .properties
+            .insert("Some".to_string(), gen.subschema_for::<T>());
+        let _ = object.required.insert("Some".to_string());
+        object.additional_properties = Some(Box::new(false.into()));
+
+        any_of(vec![
+            string_schema(&["*", "All"]),
+            tagged.into(),
+            gen.subschema_for::<T>(),
+        ])
+    }
+}
+
+/// The map form of [`Origins`]
+#[derive(Default, Serialize, JsonSchema)]
+#[serde(default)]
+struct OriginsMap {
+    /// Whether null origins are accepted
+    allow_null: bool,
+    /// Origins that must be matched exactly, or any subdomain of `https://*.acme.com`
+    exact: Option<HashSet<String>>,
+    /// Origins that are matched with regexes
+    regex: Option<HashSet<String>>,
+}
+
+impl JsonSchema for Origins {
+    fn schema_name() -> String {
+        "Origins".to_string()
+    }
+
+    /// A map of origins, or a list of exact origins on its own
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        any_of(vec![
+            gen.subschema_for::<HashSet<String>>(),
+            OriginsMap::json_schema(gen),
+        ])
+    }
+}
+
+impl JsonSchema for Method {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        "Method".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema(&[])
+    }
+}
+
+impl JsonSchema for HeaderFieldName {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        "HeaderFieldName".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema(&[])
+    }
+}
+
+/// The schema of [`CorsOptions::max_age`](crate::CorsOptions::max_age): `null`, a number of
+/// seconds, or a duration such as `"1h"`
+pub(crate) struct MaxAge;
+
+impl JsonSchema for MaxAge {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        "MaxAge".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let mut duration = SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            ..Default::default()
+        };
+        duration.string().pattern = Some(
+            r"^\s*(\d+\s*(d|days?|h|hrs?|hours?|m|mins?|minutes?|s|secs?|seconds?)\s*)+$|^\s*\d+\s*$"
+                .to_string(),
+        );
+        any_of(vec![gen.subschema_for::<Option<usize>>(), duration.into()])
+    }
+}
+
+/// The schema of [`CorsOptions::log_level`](crate::CorsOptions::log_level): `null`, or the name
+/// of a [`LevelFilter`] in upper or lower case
+pub(crate) struct LogLevel;
+
+impl JsonSchema for LogLevel {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        "LogLevel".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let names: Vec<String> = LevelFilter::iter()
+            .flat_map(|level| [level.as_str().to_string(), level.as_str().to_lowercase()])
+            .collect();
+        let mut schema = SchemaObject {
+            instance_type: Some(vec![InstanceType::String, InstanceType::Null].into()),
+            ..Default::default()
+        };
+        schema.enum_values = Some(
+            names
+                .into_iter()
+                .map(Into::into)
+                .chain(Some(().into()))
+                .collect(),
+        );
+        schema.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use schemars::schema_for;
+
+    use crate::CorsOptions;
+
+    #[test]
+    fn options_schema_is_generated() {
+        let schema = serde_json::to_value(schema_for!(CorsOptions)).expect("to not fail");
+
+        let properties = &schema["properties"];
+        assert_eq!(properties["allow_credentials"]["type"], "boolean");
+        assert_eq!(properties["log_level"]["enum"][0], "OFF");
+        assert!(properties["max_age"]["anyOf"].is_array());
+        assert!(properties.get("metrics").is_none());
+        assert!(schema["definitions"]["AllOrSome_for_Origins"]["anyOf"].is_array());
+        assert!(schema["definitions"]["Origins"]["anyOf"].is_array());
+        assert_eq!(
+            schema["definitions"]["UnmatchedOptions"]["oneOf"][0]["enum"][0],
+            "NoContent"
+        );
+    }
+}
@@ -0,0 +1,401 @@
+//! Dynamic stores of allowed origins
+//!
+//! The [`AllowedOrigins`] are fixed when [`Cors`] is created. If origins are added and removed
+//! while your application is running, such as the domains of the customers of a multi-tenant
+//! application, implement [`OriginStore`] and register it with [`CorsOptions::origin_store`].
+//!
+//! The store is only consulted for origins that are not allowed by the [`AllowedOrigins`], so
+//! origins that never change can still be configured statically. It is not consulted when all
+//! origins are allowed.
+//!
+//! Stores backed by a database or an API should be wrapped in a [`CachedOriginStore`], so that
+//! they are not queried for every request.
+//!
+//! A [`MemoryOriginStore`] keeps the origins in memory, and comes with routes to manage them while
+//! your application is running.
+//!
+//! A [`FileOriginStore`] reads the origins from a file, and can reload it whenever it is modified.
+//!
+//! # Example
+//! ```rust
+//! use std::collections::HashSet;
+//! use std::sync::{Arc, RwLock};
+//!
+//! use rocket_cors::store::{OriginStore, StoreError};
+//! use rocket_cors::CorsOptions;
+//!
+//! #[derive(Debug, Default)]
+//! struct Tenants(RwLock<HashSet<String>>);
+//!
+//! #[rocket::async_trait]
+//! impl OriginStore for Tenants {
+//!     async fn contains(&self, origin: &str) -> Result<bool, StoreError> {
+//!         Ok(self.0.read().unwrap().contains(origin))
+//!     }
+//!
+//!     fn cached(&self, origin: &str) -> Option<bool> {
+//!         Some(self.0.read().unwrap().contains(origin))
+//!     }
+//! }
+//!
+//! let tenants = Arc::new(Tenants::default());
+//! let cors = CorsOptions::default()
+//!     .allowed_origins(rocket_cors::AllowedOrigins::some_exact(&["https://www.acme.com"]))
+//!     .origin_store(tenants.clone())
+//!     .to_cors()
+//!     .expect("To not fail");
+//!
+//! // Allowed from now on, without creating `Cors` again
+//! let _ = tenants
+//!     .0
+//!     .write()
+//!     .unwrap()
+//!     .insert("https://customer.example.com".to_string());
+//! ```
+//!
+//! [`AllowedOrigins`]: crate::AllowedOrigins
+//! [`Cors`]: crate::Cors
+//! [`CorsOptions::origin_store`]: crate::CorsOptions::origin_store
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error;
+use std::fmt;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use rocket::tokio::runtime::Handle;
+
+mod file;
+mod memory;
+#[cfg(feature = "redis")]
+mod redis;
+
+pub use self::file::FileOriginStore;
+pub use self::memory::MemoryOriginStore;
+#[cfg(feature = "redis")]
+pub use self::redis::RedisOriginStore;
+
+/// An error looking up an origin in an [`OriginStore`]
+pub type StoreError = Box<dyn error::Error + Send + Sync>;
+
+/// A source of allowed origins that can change while the application is running
+///
+/// Origins are looked up by their
+/// [ASCII serialization](https://html.spec.whatwg.org/multipage/#ascii-serialisation-of-an-origin),
+/// such as `https://www.acme.com` or `http://localhost:8000`. The default port of the scheme is
+/// never included. `null` origins are never looked up.
+///
+/// The fairing and the request guard wait for [`OriginStore::contains`] before validating a
+/// request. Manual responders are run after the route handler and cannot wait, so they only use
+/// [`OriginStore::cached`]. An origin that is looked up for a request is looked up at most once,
+/// however many of these are used.
+#[rocket::async_trait]
+pub trait OriginStore: fmt::Debug + Send + Sync {
+    /// Returns whether the origin is allowed
+    ///
+    /// Origins are denied if looking them up fails.
+    async fn contains(&self, origin: &str) -> Result<bool, StoreError>;
+
+    /// Returns whether the origin is allowed, if this is known without waiting
+    ///
+    /// Returns `None` by default, so origins that are not otherwise allowed are denied when they
+    /// are validated by a manual responder alone.
+    fn cached(&self, origin: &str) -> Option<bool> {
+        let _ = origin;
+        None
+    }
+}
+
+/// An [`OriginStore`] that caches the answers of another store
+///
+/// Both allowed and denied origins are cached, for [`CachedOriginStore::ttl`] and
+/// [`CachedOriginStore::negative_ttl`] respectively. Failed lookups are not cached.
+///
+/// When an origin is not cached, [`OriginStore::cached`] starts looking it up in the background,
+/// so that it is cached for the requests that follow.
+///
+/// At most [`CachedOriginStore::capacity`] origins are cached. When the cache is full, expired
+/// origins are removed, and if none have expired, the origin that was cached first is removed.
+#[derive(Debug)]
+pub struct CachedOriginStore<S> {
+    store: Arc<S>,
+    cache: Arc<Cache>,
+    settings: Settings,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Settings {
+    ttl: Duration,
+    negative_ttl: Duration,
+    capacity: usize,
+}
+
+#[derive(Debug, Default)]
+struct Cache {
+    entries: RwLock<Entries>,
+    /// Origins being looked up in the background
+    pending: Mutex<HashSet<String>>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    origins: HashMap<String, Entry>,
+    /// The cached origins by when they were inserted
+    insertion: BTreeMap<u64, String>,
+    /// Incremented every time an origin is inserted
+    clock: u64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    allowed: bool,
+    expires_at: Instant,
+    inserted: u64,
+}
+
+impl Entries {
+    /// Inserts `origin`, removing the expired origins, and then the origin that was inserted first,
+    /// if the cache is full
+    fn insert(&mut self, origin: &str, allowed: bool, expires_at: Instant, capacity: usize) {
+        if let Some(entry) = self.origins.remove(origin) {
+            let _ = self.insertion.remove(&entry.inserted);
+        }
+        if self.origins.len() >= capacity {
+            let now = Instant::now();
+            let origins = &self.origins;
+            self.insertion
+                .retain(|_, origin| origins[origin.as_str()].expires_at > now);
+            self.origins.retain(|_, entry| entry.expires_at > now);
+            if self.origins.len() >= capacity {
+                if let Some((_, oldest)) = self.insertion.pop_first() {
+                    let _ = self.origins.remove(&oldest);
+                }
+            }
+        }
+        self.clock += 1;
+        let entry = Entry {
+            allowed,
+            expires_at,
+            inserted: self.clock,
+        };
+        let _ = self.origins.insert(origin.to_string(), entry);
+        let _ = self.insertion.insert(self.clock, origin.to_string());
+    }
+
+    fn clear(&mut self) {
+        self.origins.clear();
+        self.insertion.clear();
+    }
+}
+
+impl<S: OriginStore + 'static> CachedOriginStore<S> {
+    /// How long allowed origins are cached, unless [`CachedOriginStore::ttl`] is used
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(60);
+    /// How long denied origins are cached, unless [`CachedOriginStore::negative_ttl`] is used
+    pub const DEFAULT_NEGATIVE_TTL: Duration = Duration::from_secs(10);
+    /// How many origins are cached, unless [`CachedOriginStore::capacity`] is used
+    pub const DEFAULT_CAPACITY: usize = 10_000;
+
+    /// Cache the answers of `store`
+    pub fn new(store: S) -> Self {
+        CachedOriginStore {
+            store: Arc::new(store),
+            cache: Arc::default(),
+            settings: Settings {
+                ttl: Self::DEFAULT_TTL,
+                negative_ttl: Self::DEFAULT_NEGATIVE_TTL,
+                capacity: Self::DEFAULT_CAPACITY,
+            },
+        }
+    }
+
+    /// Sets how long allowed origins are cached
+    #[must_use]
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.settings.ttl = ttl;
+        self
+    }
+
+    /// Sets how long denied origins are cached
+    ///
+    /// Origins that are added to the store can be denied for this long.
+    #[must_use]
+    pub fn negative_ttl(mut self, negative_ttl: Duration) -> Self {
+        self.settings.negative_ttl = negative_ttl;
+        self
+    }
+
+    /// Sets how many origins are cached
+    #[must_use]
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.settings.capacity = capacity;
+        self
+    }
+
+    /// Returns the store whose answers are cached
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Remove all origins from the cache, for example after origins have been removed from the
+    /// store
+    pub fn clear(&self) {
+        self.cache
+            .entries
+            .write()
+            .expect("cache lock not to be poisoned")
+            .clear();
+    }
+}
+
+impl Cache {
+    fn get(&self, origin: &str) -> Option<bool> {
+        let entries = self.entries.read().expect("cache lock not to be poisoned");
+        entries
+            .origins
+            .get(origin)
+            .filter(|entry| entry.expires_at > Instant::now())
+            .map(|entry| entry.allowed)
+    }
+
+    fn insert(&self, origin: &str, allowed: bool, settings: Settings) {
+        let ttl = if allowed {
+            settings.ttl
+        } else {
+            settings.negative_ttl
+        };
+        self.entries
+            .write()
+            .expect("cache lock not to be poisoned")
+            .insert(origin, allowed, Instant::now() + ttl, settings.capacity);
+    }
+
+    async fn lookup<S: OriginStore>(
+        &self,
+        store: &S,
+        settings: Settings,
+        origin: &str,
+    ) -> Result<bool, StoreError> {
+        if let Some(allowed) = self.get(origin) {
+            return Ok(allowed);
+        }
+
+        let allowed = store.contains(origin).await?;
+        self.insert(origin, allowed, settings);
+        Ok(allowed)
+    }
+}
+
+#[rocket::async_trait]
+impl<S: OriginStore + 'static> OriginStore for CachedOriginStore<S> {
+    async fn contains(&self, origin: &str) -> Result<bool, StoreError> {
+        self.cache.lookup(&*self.store, self.settings, origin).await
+    }
+
+    fn cached(&self, origin: &str) -> Option<bool> {
+        if let Some(allowed) = self.cache.get(origin) {
+            return Some(allowed);
+        }
+
+        let handle = Handle::try_current().ok()?;
+        let newly_pending = self
+            .cache
+            .pending
+            .lock()
+            .expect("lock not to be poisoned")
+            .insert(origin.to_string());
+        if newly_pending {
+            let store = Arc::clone(&self.store);
+            let cache = Arc::clone(&self.cache);
+            let settings = self.settings;
+            let origin = origin.to_string();
+            drop(handle.spawn(async move {
+                if let Err(err) = cache.lookup(&*store, settings, &origin).await {
+                    error_!(
+                        "Failed to look up origin {} in the origin store: {}",
+                        origin,
+                        err
+                    );
+                }
+                let _ = cache
+                    .pending
+                    .lock()
+                    .expect("lock not to be poisoned")
+                    .remove(&origin);
+            }));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    struct CountingStore {
+        lookups: AtomicUsize,
+    }
+
+    #[rocket::async_trait]
+    impl OriginStore for CountingStore {
+        async fn contains(&self, origin: &str) -> Result<bool, StoreError> {
+            let _ = self.lookups.fetch_add(1, Ordering::SeqCst);
+            match origin {
+                "https://www.acme.com" => Ok(true),
+                "https://www.failure.com" => Err("unavailable".into()),
+                _ => Ok(false),
+            }
+        }
+    }
+
+    #[rocket::async_test]
+    async fn allowed_and_denied_origins_are_cached() {
+        let store = CachedOriginStore::new(CountingStore::default());
+
+        for _ in 0..2 {
+            assert!(store.contains("https://www.acme.com").await.unwrap());
+            assert!(!store.contains("https://www.example.com").await.unwrap());
+        }
+        assert_eq!(store.store().lookups.load(Ordering::SeqCst), 2);
+        assert_eq!(store.cached("https://www.acme.com"), Some(true));
+        assert_eq!(store.cached("https://www.example.com"), Some(false));
+
+        store.clear();
+        assert!(store.contains("https://www.acme.com").await.unwrap());
+        assert_eq!(store.store().lookups.load(Ordering::SeqCst), 3);
+    }
+
+    #[rocket::async_test]
+    async fn failed_lookups_are_not_cached() {
+        let store = CachedOriginStore::new(CountingStore::default());
+
+        assert!(store.contains("https://www.failure.com").await.is_err());
+        assert!(store.contains("https://www.failure.com").await.is_err());
+        assert_eq!(store.store().lookups.load(Ordering::SeqCst), 2);
+    }
+
+    #[rocket::async_test]
+    async fn expired_origins_are_looked_up_again() {
+        let store = CachedOriginStore::new(CountingStore::default()).ttl(Duration::ZERO);
+
+        assert!(store.contains("https://www.acme.com").await.unwrap());
+        assert!(store.contains("https://www.acme.com").await.unwrap());
+        assert_eq!(store.store().lookups.load(Ordering::SeqCst), 2);
+    }
+
+    #[rocket::async_test]
+    async fn cache_is_bounded() {
+        let store = CachedOriginStore::new(CountingStore::default()).capacity(2);
+
+        for origin in ["https://a.com", "https://b.com", "https://c.com"] {
+            assert!(!store.contains(origin).await.unwrap());
+        }
+        let entries = store.cache.entries.read().unwrap();
+        assert_eq!(entries.origins.len(), 2);
+        assert!(!entries.origins.contains_key("https://a.com"));
+        assert_eq!(entries.insertion.len(), 2);
+    }
+}
@@ -0,0 +1,240 @@
+//! An [`OriginStore`] read from a file, which is reloaded when the file changes
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+use rocket::fairing::AdHoc;
+
+use super::memory::normalize;
+use super::{OriginStore, StoreError};
+use crate::{origin_lines, read_origin_file, Error};
+
+/// An [`OriginStore`] of the origins listed in a file, one per line, which can be reloaded while
+/// your application is running
+///
+/// The file has the same format as for [`AllowedOrigins::from_file`]: blank lines are ignored,
+/// and so is everything after a `#`. Origins are matched exactly, so wildcard origins such as
+/// `https://*.acme.com` and regexes are not supported. Configure those with the
+/// [`AllowedOrigins`] instead.
+///
+/// Reload the file with [`FileOriginStore::reload`], or attach the fairing returned by
+/// [`FileOriginStore::watcher`] to reload it whenever it is modified. If the file cannot be read,
+/// or lists an invalid origin, the store keeps the origins it had.
+///
+/// # Example
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// use rocket_cors::store::FileOriginStore;
+/// use rocket_cors::CorsOptions;
+///
+/// let store = Arc::new(FileOriginStore::open("/etc/cors/origins.txt")?);
+/// let cors = CorsOptions::default()
+///     .origin_store(store.clone())
+///     .to_cors()?;
+///
+/// let _ = rocket::build()
+///     .attach(cors)
+///     .attach(store.watcher(Duration::from_secs(5)));
+/// # Ok::<(), rocket_cors::Error>(())
+/// ```
+///
+/// [`AllowedOrigins`]: crate::AllowedOrigins
+/// [`AllowedOrigins::from_file`]: crate::AllowedOrigins::from_file
+#[derive(Debug)]
+pub struct FileOriginStore {
+    path: PathBuf,
+    origins: RwLock<HashSet<String>>,
+    /// When the file was last modified, as of the last reload
+    modified: Mutex<Option<SystemTime>>,
+}
+
+impl FileOriginStore {
+    /// Create a store of the origins listed in the file at `path`
+    pub fn open<P: Into<PathBuf>>(path: P) -> Result<Self, Error> {
+        let store = FileOriginStore {
+            path: path.into(),
+            origins: RwLock::default(),
+            modified: Mutex::default(),
+        };
+        store.reload()?;
+        Ok(store)
+    }
+
+    /// Returns the path of the file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the file again, and replace the origins in the store with the ones it lists
+    ///
+    /// The origins are left unchanged if the file cannot be read, or lists an invalid origin.
+    /// This reads the file with blocking I/O.
+    pub fn reload(&self) -> Result<(), Error> {
+        // The modification time is read first, so that changes made while the file is read are
+        // picked up by the next reload
+        let modified = self.modified_at();
+
+        let contents = read_origin_file(&self.path)?;
+        let origins = origin_lines(&contents)
+            .map(normalize)
+            .collect::<Result<HashSet<_>, _>>()?;
+        *self.origins.write().expect("lock not to be poisoned") = origins;
+        // Failed reloads are retried by the watcher, even if the file is not modified again
+        *self.modified.lock().expect("lock not to be poisoned") = modified;
+        Ok(())
+    }
+
+    /// Returns the origins in the store, sorted
+    pub fn origins(&self) -> Vec<String> {
+        let mut origins: Vec<_> = self
+            .origins
+            .read()
+            .expect("lock not to be poisoned")
+            .iter()
+            .cloned()
+            .collect();
+        origins.sort();
+        origins
+    }
+
+    /// Returns a [Fairing](https://rocket.rs/guide/fairings/) that checks the file every
+    /// `interval` once the application has launched, and reloads it when it has been modified
+    ///
+    /// The file is checked and read on the blocking thread pool of the runtime.
+    pub fn watcher(self: &Arc<Self>, interval: Duration) -> AdHoc {
+        let store = Arc::clone(self);
+        AdHoc::on_liftoff("CORS Origin File Watcher", move |_| {
+            Box::pin(async move {
+                drop(rocket::tokio::spawn(async move {
+                    loop {
+                        rocket::tokio::time::sleep(interval).await;
+                        let store = Arc::clone(&store);
+                        let checked =
+                            rocket::tokio::task::spawn_blocking(move || store.reload_if_modified())
+                                .await;
+                        if let Err(err) = checked {
+                            error_!("CORS: Failed to check the origin file: {}", err);
+                        }
+                    }
+                }));
+            })
+        })
+    }
+
+    /// Reloads the file if it has been modified since the last reload
+    fn reload_if_modified(&self) {
+        let modified = self.modified_at();
+        if modified == *self.modified.lock().expect("lock not to be poisoned") {
+            return;
+        }
+
+        match self.reload() {
+            Ok(()) => info_!(
+                "CORS: Reloaded {} origins from {}",
+                self.origins.read().expect("lock not to be poisoned").len(),
+                self.path.display()
+            ),
+            Err(err) => error_!(
+                "CORS: Failed to reload the origins from {}, keeping the previous origins: {}",
+                self.path.display(),
+                err
+            ),
+        }
+    }
+
+    /// Returns when the file was last modified, if this is known
+    fn modified_at(&self) -> Option<SystemTime> {
+        fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+}
+
+#[rocket::async_trait]
+impl OriginStore for FileOriginStore {
+    async fn contains(&self, origin: &str) -> Result<bool, StoreError> {
+        Ok(self.cached(origin).unwrap_or(false))
+    }
+
+    fn cached(&self, origin: &str) -> Option<bool> {
+        Some(
+            self.origins
+                .read()
+                .expect("lock not to be poisoned")
+                .contains(origin),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Returns the path of a temporary file that no other test uses
+    fn temp_file(name: &str) -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        std::env::temp_dir().join(format!(
+            "rocket_cors_{}_{}_{}",
+            name,
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ))
+    }
+
+    #[test]
+    fn origins_are_reloaded_from_the_file() {
+        let path = temp_file("origins_are_reloaded_from_the_file");
+        fs::write(&path, "https://www.acme.com:443 # Normalized\n").expect("to write the file");
+        let store = not_err!(FileOriginStore::open(&path));
+        assert_eq!(store.origins(), vec!["https://www.acme.com".to_string()]);
+        assert_eq!(store.cached("https://www.acme.com"), Some(true));
+
+        fs::write(&path, "http://localhost:8000\nfile:///etc/passwd\n").expect("to write the file");
+        assert_matches!(store.reload(), Err(Error::OpaqueAllowedOrigin(_)));
+        assert_eq!(store.origins(), vec!["https://www.acme.com".to_string()]);
+
+        fs::write(&path, "http://localhost:8000\n").expect("to write the file");
+        not_err!(store.reload());
+        assert_eq!(store.cached("https://www.acme.com"), Some(false));
+        assert_eq!(store.cached("http://localhost:8000"), Some(true));
+
+        fs::remove_file(&path).expect("to remove the file");
+        assert_matches!(store.reload(), Err(Error::OriginFileError(..)));
+        assert_eq!(store.origins(), vec!["http://localhost:8000".to_string()]);
+    }
+
+    #[test]
+    fn failed_reloads_are_retried() {
+        let path = temp_file("failed_reloads_are_retried");
+        fs::write(&path, "https://www.acme.com\n").expect("to write the file");
+        let store = not_err!(FileOriginStore::open(&path));
+        let modified = *store.modified.lock().expect("lock not to be poisoned");
+        assert!(modified.is_some());
+
+        // The file is half-written when it is reloaded
+        fs::write(&path, "https://www.acme.com\nfile:///").expect("to write the file");
+        assert_matches!(store.reload(), Err(Error::OpaqueAllowedOrigin(_)));
+        assert_eq!(
+            *store.modified.lock().expect("lock not to be poisoned"),
+            modified
+        );
+
+        fs::write(&path, "https://www.acme.com\nhttps://api.acme.com\n")
+            .expect("to write the file");
+        not_err!(store.reload());
+        assert_eq!(
+            *store.modified.lock().expect("lock not to be poisoned"),
+            store.modified_at()
+        );
+        assert_eq!(store.cached("https://api.acme.com"), Some(true));
+
+        fs::remove_file(&path).expect("to remove the file");
+    }
+}
@@ -0,0 +1,324 @@
+//! An [`OriginStore`] kept in memory, with routes to manage it while running
+
+use std::collections::HashSet;
+use std::marker::PhantomData;
+use std::sync::{Arc, RwLock};
+
+use rocket::http::{ContentType, Method, Status};
+use rocket::outcome::Outcome;
+use rocket::request::FromRequest;
+use rocket::{route, Data, Request, Route};
+
+use super::{OriginStore, StoreError};
+use crate::Error;
+
+/// An [`OriginStore`] of origins kept in memory, which can be added and removed while your
+/// application is running
+///
+/// Origins are validated and normalized when they are added, so `https://www.acme.com:443` is
+/// stored as `https://www.acme.com`. Origins that are opaque, such as `file://` origins, cannot be
+/// added.
+///
+/// The origins can be managed from your own code, or with the routes returned by
+/// [`MemoryOriginStore::admin_routes`], so that operators can add the domain of a customer
+/// without a deploy.
+///
+/// # Example
+/// ```rust
+/// use std::sync::Arc;
+///
+/// use rocket_cors::store::MemoryOriginStore;
+/// use rocket_cors::CorsOptions;
+///
+/// let store = Arc::new(MemoryOriginStore::new());
+/// let cors = CorsOptions::default()
+///     .origin_store(store.clone())
+///     .to_cors()
+///     .expect("To not fail");
+///
+/// // Allowed from now on, without creating `Cors` again
+/// let _ = store
+///     .insert("https://customer.example.com")
+///     .expect("a valid origin");
+/// ```
+#[derive(Debug, Default)]
+pub struct MemoryOriginStore {
+    origins: RwLock<HashSet<String>>,
+}
+
+impl MemoryOriginStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an origin to the store. Returns whether the origin was not already in the store.
+    pub fn insert(&self, origin: &str) -> Result<bool, Error> {
+        let origin = normalize(origin)?;
+        Ok(self
+            .origins
+            .write()
+            .expect("lock not to be poisoned")
+            .insert(origin))
+    }
+
+    /// Remove an origin from the store. Returns whether the origin was in the store.
+    pub fn remove(&self, origin: &str) -> Result<bool, Error> {
+        let origin = normalize(origin)?;
+        Ok(self
+            .origins
+            .write()
+            .expect("lock not to be poisoned")
+            .remove(&origin))
+    }
+
+    /// Returns the origins in the store, sorted
+    pub fn origins(&self) -> Vec<String> {
+        let mut origins: Vec<_> = self
+            .origins
+            .read()
+            .expect("lock not to be poisoned")
+            .iter()
+            .cloned()
+            .collect();
+        origins.sort();
+        origins
+    }
+
+    /// Returns routes at `/origins` to manage the store, that are only served to requests for
+    /// which the [request guard](https://rocket.rs/guide/requests/#request-guards) `G` succeeds
+    ///
+    /// The routes are:
+    ///
+    /// - `GET /origins`: The origins in the store, one per line
+    /// - `PUT /origins?origin=<origin>`: Add an origin. Responds with `201 Created` if the origin
+    ///   was added, `200 OK` if it was already in the store, or `400 Bad Request` if it is not
+    ///   a valid origin.
+    /// - `DELETE /origins?origin=<origin>`: Remove an origin. Responds with `204 No Content` if
+    ///   the origin was removed, or `404 Not Found` if it was not in the store.
+    ///
+    /// If the guard fails or forwards, so does the route. Use a guard that authenticates the
+    /// operators that are allowed to manage the origins.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::sync::Arc;
+    ///
+    /// use rocket::http::Status;
+    /// use rocket::request::{FromRequest, Outcome, Request};
+    /// use rocket_cors::store::MemoryOriginStore;
+    ///
+    /// struct Operator;
+    ///
+    /// #[rocket::async_trait]
+    /// impl<'r> FromRequest<'r> for Operator {
+    ///     type Error = ();
+    ///
+    ///     async fn from_request(request: &'r Request<'_>) -> Outcome<Self, ()> {
+    ///         match request.headers().get_one("X-Api-Key") {
+    ///             Some("secret") => Outcome::Success(Operator),
+    ///             _ => Outcome::Error((Status::Unauthorized, ())),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let store = Arc::new(MemoryOriginStore::new());
+    /// let _ = rocket::build().mount("/admin", store.admin_routes::<Operator>());
+    /// ```
+    pub fn admin_routes<G>(self: &Arc<Self>) -> Vec<Route>
+    where
+        G: for<'r> FromRequest<'r> + Send + 'static,
+    {
+        [Method::Get, Method::Put, Method::Delete]
+            .into_iter()
+            .map(|method| {
+                let handler = AdminRoute::<G> {
+                    store: Arc::clone(self),
+                    guard: PhantomData,
+                };
+                Route::new(method, "/origins", handler)
+            })
+            .collect()
+    }
+}
+
+#[rocket::async_trait]
+impl OriginStore for MemoryOriginStore {
+    async fn contains(&self, origin: &str) -> Result<bool, StoreError> {
+        Ok(self.cached(origin).unwrap_or(false))
+    }
+
+    fn cached(&self, origin: &str) -> Option<bool> {
+        Some(
+            self.origins
+                .read()
+                .expect("lock not to be poisoned")
+                .contains(origin),
+        )
+    }
+}
+
+/// Validate an origin and return its
+/// [ASCII serialization](https://html.spec.whatwg.org/multipage/#ascii-serialisation-of-an-origin)
+pub(super) fn normalize(origin: &str) -> Result<String, Error> {
+    match crate::to_origin(origin)? {
+        parsed @ url::Origin::Tuple(..) => Ok(parsed.ascii_serialization()),
+        url::Origin::Opaque(_) => Err(Error::OpaqueAllowedOrigin(vec![origin.to_string()])),
+    }
+}
+
+/// Handler of the routes managing a [`MemoryOriginStore`], guarded by `G`
+struct AdminRoute<G> {
+    store: Arc<MemoryOriginStore>,
+    guard: PhantomData<fn() -> G>,
+}
+
+impl<G> Clone for AdminRoute<G> {
+    fn clone(&self) -> Self {
+        AdminRoute {
+            store: Arc::clone(&self.store),
+            guard: PhantomData,
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<G> route::Handler for AdminRoute<G>
+where
+    G: for<'r> FromRequest<'r> + Send + 'static,
+{
+    async fn handle<'r>(&self, request: &'r Request<'_>, data: Data<'r>) -> route::Outcome<'r> {
+        match request.guard::<G>().await {
+            Outcome::Success(_) => {}
+            Outcome::Error((status, _)) => return route::Outcome::Error(status),
+            Outcome::Forward(status) => return route::Outcome::Forward((data, status)),
+        }
+
+        if request.method() == Method::Get {
+            let origins = self.store.origins().join("\n");
+            return route::Outcome::from(request, (ContentType::Plain, origins));
+        }
+
+        let origin = match request.query_value::<&str>("origin") {
+            Some(Ok(origin)) => origin,
+            _ => {
+                let message = "The `origin` query parameter is required";
+                return route::Outcome::from(request, (Status::BadRequest, message));
+            }
+        };
+        let result = match request.method() {
+            Method::Put => self.store.insert(origin).map(|inserted| {
+                if inserted {
+                    info_!("CORS: Added {} to the origin store", origin);
+                    Status::Created
+                } else {
+                    Status::Ok
+                }
+            }),
+            _ => self.store.remove(origin).map(|removed| {
+                if removed {
+                    info_!("CORS: Removed {} from the origin store", origin);
+                    Status::NoContent
+                } else {
+                    Status::NotFound
+                }
+            }),
+        };
+        match result {
+            Ok(status) => route::Outcome::from(request, status),
+            Err(err) => route::Outcome::from(request, (Status::BadRequest, err.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocket::http::Header;
+    use rocket::local::blocking::Client;
+    use rocket::request;
+
+    use super::*;
+
+    struct Operator;
+
+    #[rocket::async_trait]
+    impl<'r> FromRequest<'r> for Operator {
+        type Error = ();
+
+        async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, ()> {
+            match request.headers().get_one("X-Api-Key") {
+                Some("secret") => Outcome::Success(Operator),
+                _ => Outcome::Error((Status::Unauthorized, ())),
+            }
+        }
+    }
+
+    #[test]
+    fn origins_are_normalized_and_validated() {
+        let store = MemoryOriginStore::new();
+        assert!(not_err!(store.insert("https://www.acme.com:443")));
+        assert!(!not_err!(store.insert("https://www.acme.com")));
+        assert_matches!(
+            store.insert("file:///etc/passwd"),
+            Err(Error::OpaqueAllowedOrigin(_))
+        );
+        assert_matches!(store.insert("acme.com"), Err(Error::BadOrigin(_)));
+
+        assert_eq!(store.origins(), vec!["https://www.acme.com".to_string()]);
+        assert_eq!(store.cached("https://www.acme.com"), Some(true));
+        assert_eq!(store.cached("https://www.example.com"), Some(false));
+
+        assert!(not_err!(store.remove("https://www.acme.com")));
+        assert!(!not_err!(store.remove("https://www.acme.com")));
+        assert!(store.origins().is_empty());
+    }
+
+    #[test]
+    fn admin_routes_manage_the_store() {
+        let store = Arc::new(MemoryOriginStore::new());
+        let rocket = rocket::build().mount("/admin", store.admin_routes::<Operator>());
+        let client = Client::tracked(rocket).expect("valid rocket instance");
+        let key = || Header::new("X-Api-Key", "secret");
+
+        let response = client
+            .put("/admin/origins?origin=https://www.acme.com")
+            .dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+        assert!(store.origins().is_empty());
+
+        for (origin, status) in [
+            ("https://www.acme.com", Status::Created),
+            ("https://www.acme.com:443", Status::Ok),
+            ("http://localhost:8000", Status::Created),
+            ("file:///etc/passwd", Status::BadRequest),
+        ] {
+            let response = client
+                .put(format!("/admin/origins?origin={}", origin))
+                .header(key())
+                .dispatch();
+            assert_eq!(response.status(), status);
+        }
+
+        let response = client.get("/admin/origins").header(key()).dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.into_string(),
+            Some("http://localhost:8000\nhttps://www.acme.com".to_string())
+        );
+
+        let response = client
+            .delete("/admin/origins?origin=http://localhost:8000")
+            .header(key())
+            .dispatch();
+        assert_eq!(response.status(), Status::NoContent);
+        let response = client
+            .delete("/admin/origins?origin=http://localhost:8000")
+            .header(key())
+            .dispatch();
+        assert_eq!(response.status(), Status::NotFound);
+        let response = client.delete("/admin/origins").header(key()).dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+
+        assert_eq!(store.origins(), vec!["https://www.acme.com".to_string()]);
+    }
+}
@@ -0,0 +1,241 @@
+//! An [`OriginStore`] backed by a Redis set, enabled with the `redis` feature
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use redis::aio::MultiplexedConnection;
+use redis::{AsyncCommands, Client, IntoConnectionInfo, RedisResult};
+use rocket::tokio::runtime::Handle;
+use rocket::tokio::sync::Mutex;
+
+use super::{OriginStore, StoreError};
+
+/// An [`OriginStore`] that looks up the allowed origins in a
+/// [Redis set](https://redis.io/docs/data-types/sets/)
+///
+/// The members of the set are the
+/// [ASCII serialization](https://html.spec.whatwg.org/multipage/#ascii-serialisation-of-an-origin)
+/// of the allowed origins, such as `https://www.acme.com`, and can be managed with `SADD` and
+/// `SREM`.
+///
+/// Origins are looked up in a local copy of the set, so that requests never wait for Redis. When
+/// the copy is older than the TTL, the next lookup starts fetching the set again in the background
+/// and is answered from the old copy in the meantime. If Redis cannot be reached, the old copy
+/// keeps being used.
+///
+/// The set is fetched by the first lookup, which waits for it. You can also call
+/// [`RedisOriginStore::refresh`] to fetch it before you start serving requests.
+///
+/// This store does its own caching, so it does not need to be wrapped in a
+/// [`CachedOriginStore`](super::CachedOriginStore).
+///
+/// # Example
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// use rocket_cors::store::RedisOriginStore;
+/// use rocket_cors::CorsOptions;
+///
+/// # async fn cors() -> rocket_cors::Cors {
+/// let store = RedisOriginStore::open("redis://127.0.0.1/", "cors:origins")
+///     .expect("a valid Redis URL")
+///     .ttl(Duration::from_secs(10));
+/// store.refresh().await.expect("Redis to be reachable");
+///
+/// CorsOptions::default()
+///     .origin_store(Arc::new(store))
+///     .to_cors()
+///     .expect("To not fail")
+/// # }
+/// # let _ = cors;
+/// ```
+#[derive(Debug)]
+pub struct RedisOriginStore {
+    inner: Arc<Inner>,
+    ttl: Duration,
+}
+
+#[derive(Debug)]
+struct Inner {
+    client: Client,
+    key: String,
+    connection: Mutex<Option<MultiplexedConnection>>,
+    cache: RwLock<Cache>,
+    refreshing: AtomicBool,
+}
+
+#[derive(Debug, Default)]
+struct Cache {
+    origins: HashSet<String>,
+    fetched_at: Option<Instant>,
+}
+
+impl RedisOriginStore {
+    /// The TTL of the local copy of the set, unless [`RedisOriginStore::ttl`] is used
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(30);
+
+    /// Create a store looking up the origins in the set at `key` of the Redis server at `info`,
+    /// which is usually a URL such as `redis://127.0.0.1/`
+    ///
+    /// No connection is made until the set is first fetched.
+    pub fn open<T: IntoConnectionInfo>(info: T, key: &str) -> RedisResult<Self> {
+        Ok(Self::new(Client::open(info)?, key))
+    }
+
+    /// Create a store looking up the origins in the set at `key` with a Redis client
+    pub fn new(client: Client, key: &str) -> Self {
+        RedisOriginStore {
+            inner: Arc::new(Inner {
+                client,
+                key: key.to_string(),
+                connection: Mutex::new(None),
+                cache: RwLock::new(Cache::default()),
+                refreshing: AtomicBool::new(false),
+            }),
+            ttl: Self::DEFAULT_TTL,
+        }
+    }
+
+    /// Sets how long the local copy of the set is used before it is fetched again
+    #[must_use]
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Fetch the set from Redis and replace the local copy with it
+    pub async fn refresh(&self) -> RedisResult<()> {
+        self.inner.refresh().await
+    }
+}
+
+impl Inner {
+    async fn refresh(&self) -> RedisResult<()> {
+        let mut connection = self.connection.lock().await;
+        let conn = match connection.as_mut() {
+            Some(conn) => conn,
+            None => connection.insert(self.client.get_multiplexed_tokio_connection().await?),
+        };
+
+        match conn.smembers::<_, HashSet<String>>(&self.key).await {
+            Ok(origins) => {
+                let mut cache = self.cache.write().expect("cache lock not to be poisoned");
+                cache.origins = origins;
+                cache.fetched_at = Some(Instant::now());
+                Ok(())
+            }
+            Err(err) => {
+                // Connect again on the next refresh
+                *connection = None;
+                Err(err)
+            }
+        }
+    }
+
+    /// Start fetching the set in the background, unless it is already being fetched
+    fn spawn_refresh(self: &Arc<Self>) {
+        let handle = match Handle::try_current() {
+            Ok(handle) => handle,
+            Err(_) => return,
+        };
+        if self.refreshing.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let inner = Arc::clone(self);
+        drop(handle.spawn(async move {
+            if let Err(err) = inner.refresh().await {
+                error_!("Failed to fetch allowed origins from Redis: {}", err);
+            }
+            inner.refreshing.store(false, Ordering::Release);
+        }));
+    }
+}
+
+impl RedisOriginStore {
+    /// Look up the origin in the local copy of the set, or returns `None` if the set has never been
+    /// fetched. Starts fetching the set in the background if the local copy is stale.
+    fn lookup(&self, origin: &str) -> Option<bool> {
+        let (found, fetched_at) = {
+            let cache = self
+                .inner
+                .cache
+                .read()
+                .expect("cache lock not to be poisoned");
+            (cache.origins.contains(origin), cache.fetched_at)
+        };
+
+        let stale = fetched_at.map_or(true, |fetched_at| fetched_at.elapsed() >= self.ttl);
+        if stale {
+            self.inner.spawn_refresh();
+        }
+        fetched_at.map(|_| found)
+    }
+}
+
+#[rocket::async_trait]
+impl OriginStore for RedisOriginStore {
+    async fn contains(&self, origin: &str) -> Result<bool, StoreError> {
+        let fetched = self
+            .inner
+            .cache
+            .read()
+            .expect("cache lock not to be poisoned")
+            .fetched_at
+            .is_some();
+        if !fetched {
+            self.refresh().await?;
+        }
+        Ok(self.lookup(origin).unwrap_or(false))
+    }
+
+    fn cached(&self, origin: &str) -> Option<bool> {
+        self.lookup(origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> RedisOriginStore {
+        // Nothing listens on the discard port, so fetching always fails
+        RedisOriginStore::open("redis://127.0.0.1:9/", "cors:origins").expect("a valid URL")
+    }
+
+    fn fill(store: &RedisOriginStore) {
+        let mut cache = store.inner.cache.write().unwrap();
+        let _ = cache.origins.insert("https://www.acme.com".to_string());
+        cache.fetched_at = Some(Instant::now());
+    }
+
+    #[test]
+    fn origins_are_looked_up_in_the_local_copy() {
+        let store = store();
+        fill(&store);
+
+        assert_eq!(store.cached("https://www.acme.com"), Some(true));
+        assert_eq!(store.cached("https://www.example.com"), Some(false));
+        assert!(!store.inner.refreshing.load(Ordering::Acquire));
+    }
+
+    #[rocket::async_test]
+    async fn failed_refresh_keeps_the_local_copy() {
+        let store = store().ttl(Duration::ZERO);
+        fill(&store);
+
+        assert!(store.refresh().await.is_err());
+        assert!(store.contains("https://www.acme.com").await.unwrap());
+    }
+
+    #[rocket::async_test]
+    async fn first_lookup_waits_for_the_set() {
+        let store = store();
+
+        assert!(store.contains("https://www.acme.com").await.is_err());
+        assert_eq!(store.cached("https://www.acme.com"), None);
+    }
+}
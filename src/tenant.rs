@@ -0,0 +1,278 @@
+//! CORS policies that depend on the `Host` of the request
+//!
+//! A multi-tenant application served on subdomains, such as `tenant1.api.acme.com`, might allow
+//! different origins for each tenant. Implement [`TenantPolicyResolver`] to map the host of a
+//! request to the [`Cors`] policy of its tenant, and attach a [`TenantFairing`] instead of the
+//! `Cors` fairing.
+//!
+//! Behind a reverse proxy, give the fairing the [`TrustedProxies`] to resolve policies for the
+//! host that clients sent requests to, as reported by the proxy, rather than the `Host` of the
+//! request.
+//!
+//! # Example
+//! ```rust
+//! use std::collections::HashMap;
+//! use std::sync::Arc;
+//!
+//! use rocket_cors::tenant::{TenantFairing, TenantPolicyResolver};
+//! use rocket_cors::{AllowedOrigins, Cors, CorsOptions};
+//!
+//! struct Tenants(HashMap<String, Arc<Cors>>);
+//!
+//! #[rocket::async_trait]
+//! impl TenantPolicyResolver for Tenants {
+//!     async fn resolve(&self, host: &str) -> Option<Arc<Cors>> {
+//!         self.0.get(host).cloned()
+//!     }
+//! }
+//!
+//! let tenant1 = CorsOptions::default()
+//!     .allowed_origins(AllowedOrigins::some_exact(&["https://www.tenant1.com"]))
+//!     .to_cors()
+//!     .expect("To not fail");
+//! let tenants = Tenants(
+//!     [("tenant1.api.acme.com".to_string(), Arc::new(tenant1))]
+//!         .into_iter()
+//!         .collect(),
+//! );
+//!
+//! // Hosts without a tenant only allow the main site
+//! let default = CorsOptions::strict(&["https://www.acme.com"])
+//!     .to_cors()
+//!     .expect("To not fail");
+//!
+//! let _ = rocket::build().attach(TenantFairing::new(tenants, default));
+//! ```
+
+use std::sync::Arc;
+
+use rocket::{Data, Request, Response};
+
+use crate::fairing::{on_request_wrapper, on_response_wrapper};
+use crate::forwarded::{ForwardedHeader, TrustedProxies};
+use crate::Cors;
+
+/// Resolves the [`Cors`] policy for the host of a request
+///
+/// The resolver is called for every request, so policies that are looked up in a database or an
+/// API should be cached.
+#[rocket::async_trait]
+pub trait TenantPolicyResolver: Send + Sync + 'static {
+    /// Returns the policy for `host`, or `None` to use the default policy of the
+    /// [`TenantFairing`]
+    ///
+    /// The host is the domain of the `Host` header, or of the forwarded host of a request from one
+    /// of the [`TrustedProxies`](TenantFairing::trusted_proxies), in lower case and without the
+    /// port.
+    async fn resolve(&self, host: &str) -> Option<Arc<Cors>>;
+}
+
+/// A [Fairing](https://rocket.rs/guide/fairings/) that validates each request with the policy
+/// that a [`TenantPolicyResolver`] resolves for its host
+///
+/// Requests without a host, or with a host that the resolver has no policy for, are
+/// validated with the default policy.
+///
+/// Attach this fairing instead of the `Cors` fairing, not in addition to it.
+pub struct TenantFairing {
+    resolver: Box<dyn TenantPolicyResolver>,
+    default: Arc<Cors>,
+    trusted_proxies: TrustedProxies,
+    forwarded_header: ForwardedHeader,
+}
+
+impl TenantFairing {
+    /// Create a fairing that resolves policies with `resolver`, and uses `default` for the hosts
+    /// that it has no policy for
+    pub fn new<R: TenantPolicyResolver>(resolver: R, default: Cors) -> Self {
+        TenantFairing {
+            resolver: Box::new(resolver),
+            default: Arc::new(default),
+            trusted_proxies: TrustedProxies::None,
+            forwarded_header: ForwardedHeader::Forwarded,
+        }
+    }
+
+    /// Sets the proxies whose [`forwarded_header`](TenantFairing::forwarded_header) is used to
+    /// find the host of a request. None by default.
+    #[must_use]
+    pub fn trusted_proxies(mut self, trusted_proxies: TrustedProxies) -> Self {
+        self.trusted_proxies = trusted_proxies;
+        self
+    }
+
+    /// Sets the header that the trusted proxies set to the host of a request. The `Forwarded`
+    /// header by default.
+    #[must_use]
+    pub fn forwarded_header(mut self, forwarded_header: ForwardedHeader) -> Self {
+        self.forwarded_header = forwarded_header;
+        self
+    }
+
+    /// Returns the policy to validate `request` with
+    async fn policy(&self, request: &Request<'_>) -> Arc<Cors> {
+        let host = match self.trusted_proxies.host(request, self.forwarded_header) {
+            Some(host) => host.domain().as_str().to_ascii_lowercase(),
+            None => return Arc::clone(&self.default),
+        };
+        match self.resolver.resolve(&host).await {
+            Some(policy) => policy,
+            None => {
+                debug_!("CORS Tenant Fairing: No policy for host {}", host);
+                Arc::clone(&self.default)
+            }
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl rocket::fairing::Fairing for TenantFairing {
+    fn info(&self) -> rocket::fairing::Info {
+        rocket::fairing::Info {
+            name: "CORS Tenants",
+            kind: rocket::fairing::Kind::Ignite
+                | rocket::fairing::Kind::Liftoff
+                | rocket::fairing::Kind::Request
+                | rocket::fairing::Kind::Response,
+        }
+    }
+
+    async fn on_ignite(&self, rocket: rocket::Rocket<rocket::Build>) -> rocket::fairing::Result {
+        rocket::fairing::Fairing::on_ignite(&*self.default, rocket).await
+    }
+
+    async fn on_liftoff(&self, rocket: &rocket::Rocket<rocket::Orbit>) {
+        rocket::fairing::Fairing::on_liftoff(&*self.default, rocket).await
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        let policy = self.policy(request).await;
+        on_request_wrapper(&policy, request).await;
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        on_response_wrapper(request, response).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocket::http::uri::Host;
+    use rocket::http::{Header, Status};
+    use rocket::local::blocking::Client;
+
+    use super::*;
+    use crate::{AllowedOrigins, CorsOptions};
+
+    struct Tenant;
+
+    #[rocket::async_trait]
+    impl TenantPolicyResolver for Tenant {
+        async fn resolve(&self, host: &str) -> Option<Arc<Cors>> {
+            if host != "tenant.api.acme.com" {
+                return None;
+            }
+            let cors = CorsOptions::default()
+                .allowed_origins(AllowedOrigins::some_exact(&["https://www.tenant.com"]))
+                .to_cors()
+                .expect("To not fail");
+            Some(Arc::new(cors))
+        }
+    }
+
+    #[rocket::get("/")]
+    fn index() -> &'static str {
+        "Hello CORS"
+    }
+
+    fn client() -> Client {
+        let default = CorsOptions::default()
+            .allowed_origins(AllowedOrigins::some_exact(&["https://www.acme.com"]))
+            .to_cors()
+            .expect("To not fail");
+        let rocket = rocket::build()
+            .mount("/", rocket::routes![index])
+            .attach(TenantFairing::new(Tenant, default));
+        Client::tracked(rocket).expect("valid rocket instance")
+    }
+
+    #[test]
+    fn requests_are_validated_with_the_policy_of_their_host() {
+        let client = client();
+
+        for (host, origin, allowed) in [
+            ("Tenant.api.acme.com:8000", "https://www.tenant.com", true),
+            ("tenant.api.acme.com", "https://www.acme.com", false),
+            ("other.api.acme.com", "https://www.acme.com", true),
+            ("other.api.acme.com", "https://www.tenant.com", false),
+        ] {
+            let mut request = client.get("/").header(Header::new("Origin", origin));
+            let host = Host::parse(host).expect("to be valid");
+            request.inner_mut().set_host(host);
+            let response = request.dispatch();
+            if allowed {
+                assert_eq!(response.status(), Status::Ok);
+                assert_eq!(
+                    response.headers().get_one("Access-Control-Allow-Origin"),
+                    Some(origin)
+                );
+            } else {
+                assert_eq!(response.status(), Status::Forbidden);
+            }
+        }
+    }
+
+    #[test]
+    fn forwarded_hosts_are_used_from_trusted_proxies() {
+        let default = CorsOptions::default()
+            .allowed_origins(AllowedOrigins::some_exact(&["https://www.acme.com"]))
+            .to_cors()
+            .expect("To not fail");
+        let fairing = TenantFairing::new(Tenant, default).trusted_proxies(TrustedProxies::All);
+        let rocket = rocket::build()
+            .mount("/", rocket::routes![index])
+            .attach(fairing);
+        let proxied = Client::tracked(rocket).expect("valid rocket instance");
+
+        let response = proxied
+            .get("/")
+            .header(Header::new(
+                "Forwarded",
+                "proto=https;host=tenant.api.acme.com",
+            ))
+            .header(Header::new("Origin", "https://www.tenant.com"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            Some("https://www.tenant.com")
+        );
+
+        // The forwarded host is ignored by default
+        let client = client();
+        let response = client
+            .get("/")
+            .header(Header::new(
+                "Forwarded",
+                "proto=https;host=tenant.api.acme.com",
+            ))
+            .header(Header::new("Origin", "https://www.tenant.com"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+    }
+
+    #[test]
+    fn requests_without_host_use_the_default_policy() {
+        let client = client();
+
+        let response = client
+            .get("/")
+            .header(Header::new("Origin", "https://www.acme.com"))
+            .dispatch();
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            Some("https://www.acme.com")
+        );
+    }
+}
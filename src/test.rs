@@ -0,0 +1,231 @@
+//! Builders for CORS requests in tests, enabled with the `testing` feature
+//!
+//! The builders add the `Origin`, `Access-Control-Request-Method` and
+//! `Access-Control-Request-Headers` headers to requests made with Rocket's
+//! [local clients](rocket::local), so that your own tests do not have to.
+//!
+//! # Example
+//! ```rust
+//! use rocket::http::Method;
+//! use rocket::local::blocking::Client;
+//! use rocket_cors::test::{actual, preflight};
+//! use rocket_cors::CorsOptions;
+//!
+//! #[rocket::get("/")]
+//! fn index() -> &'static str {
+//!     "Hello CORS"
+//! }
+//!
+//! fn main() {
+//!     let cors = CorsOptions::default().to_cors().expect("To not fail");
+//!     let rocket = rocket::build()
+//!         .mount("/", rocket::routes![index])
+//!         .attach(cors);
+//!     let client = Client::tracked(rocket).expect("valid rocket instance");
+//!
+//!     let response = preflight("/")
+//!         .origin("https://www.acme.com")
+//!         .method(Method::Get)
+//!         .headers(["Authorization"])
+//!         .blocking(&client)
+//!         .dispatch();
+//!     assert!(response.status().class().is_success());
+//!
+//!     let response = actual(Method::Get, "/")
+//!         .origin("https://www.acme.com")
+//!         .blocking(&client)
+//!         .dispatch();
+//!     assert_eq!(
+//!         response.headers().get_one("Access-Control-Allow-Origin"),
+//!         Some("https://www.acme.com")
+//!     );
+//! }
+//! ```
+
+use rocket::http::{Header, Method};
+use rocket::local::{asynchronous, blocking};
+
+/// A CORS request that is being built, created with [`preflight`] or [`actual`]
+#[derive(Clone, Debug)]
+pub struct CorsRequest {
+    method: Method,
+    path: String,
+    origin: Option<String>,
+    request_method: Option<Method>,
+    request_headers: Vec<String>,
+}
+
+/// Start building a pre-flight `OPTIONS` request to `path`
+pub fn preflight<S: Into<String>>(path: S) -> CorsRequest {
+    CorsRequest::new(Method::Options, path.into())
+}
+
+/// Start building an actual request to `path`
+pub fn actual<S: Into<String>>(method: Method, path: S) -> CorsRequest {
+    CorsRequest::new(method, path.into())
+}
+
+impl CorsRequest {
+    fn new(method: Method, path: String) -> Self {
+        CorsRequest {
+            method,
+            path,
+            origin: None,
+            request_method: None,
+            request_headers: vec![],
+        }
+    }
+
+    /// Sets the `Origin` header
+    #[must_use]
+    pub fn origin<S: Into<String>>(mut self, origin: S) -> Self {
+        self.origin = Some(origin.into());
+        self
+    }
+
+    /// Sets the `Access-Control-Request-Method` header
+    #[must_use]
+    pub fn method(mut self, method: Method) -> Self {
+        self.request_method = Some(method);
+        self
+    }
+
+    /// Sets the `Access-Control-Request-Headers` header
+    #[must_use]
+    pub fn headers<I, S>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.request_headers = headers.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Returns the CORS headers of the request
+    pub fn cors_headers(&self) -> Vec<Header<'static>> {
+        let mut headers = vec![];
+        if let Some(ref origin) = self.origin {
+            headers.push(Header::new("Origin", origin.clone()));
+        }
+        if let Some(method) = self.request_method {
+            headers.push(Header::new(
+                "Access-Control-Request-Method",
+                method.as_str(),
+            ));
+        }
+        if !self.request_headers.is_empty() {
+            headers.push(Header::new(
+                "Access-Control-Request-Headers",
+                self.request_headers.join(", "),
+            ));
+        }
+        headers
+    }
+
+    /// Create the request with a blocking [`Client`](blocking::Client)
+    pub fn blocking(self, client: &blocking::Client) -> blocking::LocalRequest<'_> {
+        let headers = self.cors_headers();
+        let mut request = client.req(self.method, self.path);
+        for header in headers {
+            request.add_header(header);
+        }
+        request
+    }
+
+    /// Create the request with an asynchronous [`Client`](asynchronous::Client)
+    pub fn asynchronous(self, client: &asynchronous::Client) -> asynchronous::LocalRequest<'_> {
+        let headers = self.cors_headers();
+        let mut request = client.req(self.method, self.path);
+        for header in headers {
+            request.add_header(header);
+        }
+        request
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocket::http::Status;
+
+    use super::*;
+    use crate::{AllowedOrigins, CorsOptions};
+
+    fn rocket() -> rocket::Rocket<rocket::Build> {
+        let cors = CorsOptions::default()
+            .allowed_origins(AllowedOrigins::some_exact(&["https://www.acme.com"]))
+            .allowed_headers(crate::AllowedHeaders::some(&["Authorization"]))
+            .to_cors()
+            .expect("To not fail");
+        rocket::build().attach(cors)
+    }
+
+    #[test]
+    fn cors_headers_are_set() {
+        let request = preflight("/")
+            .origin("https://www.acme.com")
+            .method(Method::Post)
+            .headers(["Authorization", "X-Custom"]);
+        let headers: Vec<_> = request
+            .cors_headers()
+            .into_iter()
+            .map(|header| (header.name.to_string(), header.value.to_string()))
+            .collect();
+
+        assert_eq!(
+            headers,
+            vec![
+                ("Origin".to_string(), "https://www.acme.com".to_string()),
+                (
+                    "Access-Control-Request-Method".to_string(),
+                    "POST".to_string()
+                ),
+                (
+                    "Access-Control-Request-Headers".to_string(),
+                    "Authorization, X-Custom".to_string()
+                ),
+            ]
+        );
+        assert!(actual(Method::Get, "/").cors_headers().is_empty());
+    }
+
+    #[test]
+    fn blocking_requests_are_validated() {
+        let client = blocking::Client::tracked(rocket()).expect("valid rocket instance");
+
+        let response = preflight("/")
+            .origin("https://www.acme.com")
+            .method(Method::Get)
+            .headers(["Authorization"])
+            .blocking(&client)
+            .dispatch();
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            Some("https://www.acme.com")
+        );
+
+        let response = preflight("/")
+            .origin("https://www.example.com")
+            .method(Method::Get)
+            .blocking(&client)
+            .dispatch();
+        assert_eq!(response.status(), Status::Forbidden);
+    }
+
+    #[rocket::async_test]
+    async fn asynchronous_requests_are_validated() {
+        let client = asynchronous::Client::tracked(rocket())
+            .await
+            .expect("valid rocket instance");
+
+        let response = preflight("/")
+            .origin("https://www.acme.com")
+            .method(Method::Get)
+            .asynchronous(&client)
+            .dispatch()
+            .await;
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            Some("https://www.acme.com")
+        );
+    }
+}
@@ -104,6 +104,106 @@ fn cors_options_check() {
     assert_eq!("https://www.acme.com", origin_header);
 }
 
+/// Pre-flight requests for paths without any route are not turned into a 204
+#[test]
+fn cors_options_unknown_path_is_not_found() {
+    let client = Client::tracked(rocket()).unwrap();
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+    let method_header = Header::new(
+        ACCESS_CONTROL_REQUEST_METHOD.as_str(),
+        hyper::Method::GET.as_str(),
+    );
+    let req = client
+        .options("/unknown")
+        .header(origin_header)
+        .header(method_header);
+
+    let response = req.dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+}
+
+#[test]
+fn cors_options_unmatched_options_are_configurable() {
+    let make_client = |unmatched_options| {
+        let cors = CorsOptions::default()
+            .unmatched_options(unmatched_options)
+            .to_cors()
+            .expect("To not fail");
+        Client::tracked(rocket::build().mount("/", routes![cors]).attach(cors)).unwrap()
+    };
+    let preflight = |client: &Client| {
+        client
+            .options("/")
+            .header(Header::new(ORIGIN.as_str(), "https://www.acme.com"))
+            .header(Header::new(
+                ACCESS_CONTROL_REQUEST_METHOD.as_str(),
+                hyper::Method::GET.as_str(),
+            ))
+            .dispatch()
+            .status()
+    };
+
+    let client = make_client(UnmatchedOptions::NoContent);
+    assert_eq!(preflight(&client), Status::NoContent);
+
+    let client = make_client(UnmatchedOptions::NotFound);
+    assert_eq!(preflight(&client), Status::NotFound);
+
+    let client = make_client(UnmatchedOptions::MethodNotAllowed);
+    let response = client
+        .options("/")
+        .header(Header::new(ORIGIN.as_str(), "https://www.acme.com"))
+        .header(Header::new(
+            ACCESS_CONTROL_REQUEST_METHOD.as_str(),
+            hyper::Method::GET.as_str(),
+        ))
+        .dispatch();
+    assert_eq!(response.status(), Status::MethodNotAllowed);
+    assert_eq!(response.headers().get_one("Allow"), Some("GET"));
+}
+
+/// The CORS decision of the fairing, as a request guard
+struct CorsDecision(Option<Decision>);
+
+#[rocket::async_trait]
+impl<'r> rocket::request::FromRequest<'r> for CorsDecision {
+    type Error = ();
+
+    async fn from_request(
+        request: &'r rocket::Request<'_>,
+    ) -> rocket::request::Outcome<Self, Self::Error> {
+        rocket::request::Outcome::Success(CorsDecision(rocket_cors::decision(request)))
+    }
+}
+
+#[get("/decision")]
+fn decision(decision: CorsDecision) -> String {
+    match decision.0 {
+        Some(Decision::Allowed { origin, .. }) => format!("allowed {}", origin),
+        Some(decision) => format!("{:?}", decision),
+        None => "none".to_string(),
+    }
+}
+
+#[test]
+fn cors_decision_is_exposed() {
+    let rocket = rocket::build()
+        .mount("/", routes![decision])
+        .attach(make_cors());
+    let client = Client::tracked(rocket).unwrap();
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+    let response = client.get("/decision").header(origin_header).dispatch();
+    assert_eq!(
+        response.into_string(),
+        Some("allowed https://www.acme.com".to_string())
+    );
+
+    let response = client.get("/decision").dispatch();
+    assert_eq!(response.into_string(), Some("none".to_string()));
+}
+
 #[test]
 fn cors_get_check() {
     let client = Client::tracked(rocket()).unwrap();
@@ -181,6 +281,27 @@ fn cors_options_missing_origin() {
         .is_none());
 }
 
+/// An `OPTIONS` request without `Access-Control-Request-Method` is not a pre-flight request, so it
+/// is handled like any other request
+#[test]
+fn cors_options_missing_request_method() {
+    let client = Client::tracked(rocket()).unwrap();
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+    let response = client.options("/").header(origin_header).dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+
+    let origin_header = response
+        .headers()
+        .get_one("Access-Control-Allow-Origin")
+        .expect("to exist");
+    assert_eq!("https://www.acme.com", origin_header);
+    assert!(response
+        .headers()
+        .get_one("Access-Control-Allow-Methods")
+        .is_none());
+}
+
 #[test]
 fn cors_options_bad_request_method() {
     let client = Client::tracked(rocket()).unwrap();
@@ -243,6 +364,89 @@ fn cors_get_bad_origin() {
         .headers()
         .get_one("Access-Control-Allow-Origin")
         .is_none());
+    assert_eq!(response.headers().get_one("Vary"), Some("Origin"));
+}
+
+#[test]
+fn invalid_origins_are_ignored_if_configured() {
+    let client = Client::tracked(rocket()).unwrap();
+    let response = client
+        .get("/")
+        .header(Header::new(ORIGIN.as_str(), "not an origin"))
+        .dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+
+    let lenient = CorsOptions::default()
+        .allowed_origins(AllowedOrigins::some_exact(&["https://www.acme.com"]))
+        .on_invalid_origin(InvalidOrigin::Ignore)
+        .to_cors()
+        .expect("To not fail");
+    let rocket = rocket::build().mount("/", routes![cors]).attach(lenient);
+    let client = Client::tracked(rocket).unwrap();
+    let response = client
+        .get("/")
+        .header(Header::new(ORIGIN.as_str(), "not an origin"))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response
+        .headers()
+        .get_one("Access-Control-Allow-Origin")
+        .is_none());
+    assert_eq!(response.into_string(), Some("Hello CORS".to_string()));
+}
+
+#[test]
+fn non_cors_responses_vary_on_origin_if_configured() {
+    let client = Client::tracked(rocket()).unwrap();
+    let response = client.get("/").dispatch();
+    assert!(response.status().class().is_success());
+    assert!(response.headers().get_one("Vary").is_none());
+
+    let options = make_cors().to_options().always_vary_origin(true);
+    let client = Client::tracked(
+        rocket::build()
+            .mount("/", routes![cors])
+            .attach(options.to_cors().expect("To not fail")),
+    )
+    .unwrap();
+    let response = client.get("/").dispatch();
+    assert!(response.status().class().is_success());
+    assert_eq!(response.headers().get_one("Vary"), Some("Origin"));
+    assert!(response
+        .headers()
+        .get_one("Access-Control-Allow-Origin")
+        .is_none());
+}
+
+#[rocket::post("/post")]
+fn post_route<'a>() -> &'a str {
+    "Not for failing requests"
+}
+
+#[test]
+fn cors_post_bad_method_is_denied_when_enforced() {
+    let cors = CorsOptions {
+        allowed_origins: AllowedOrigins::some_exact(&["https://www.acme.com"]),
+        allowed_methods: vec![Method::Get].into_iter().map(From::from).collect(),
+        enforce_on_actual_requests: true,
+        ..Default::default()
+    }
+    .to_cors()
+    .expect("To not fail");
+    let client =
+        Client::tracked(rocket::build().mount("/", routes![post_route]).attach(cors)).unwrap();
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+    let response = client.post("/post").header(origin_header).dispatch();
+    assert_eq!(response.status(), Status::Forbidden);
+    assert!(response
+        .headers()
+        .get_one("Access-Control-Allow-Origin")
+        .is_none());
+    assert_ne!(
+        response.into_string().as_deref(),
+        Some("Not for failing requests")
+    );
 }
 
 /// This test ensures that on a failing CORS request, the route (along with its side effects)
@@ -271,3 +475,470 @@ fn routes_failing_checks_are_not_executed() {
         .get_one("Access-Control-Allow-Origin")
         .is_none());
 }
+
+#[get("/<_..>", rank = 100)]
+fn catch_all_route<'a>() -> &'a str {
+    "Not for failing requests"
+}
+
+#[rocket::catch(403)]
+fn forbidden<'a>() -> &'a str {
+    "Forbidden by CORS"
+}
+
+/// The responses of the routes to failing requests are replaced with the catcher of the error
+#[test]
+fn failing_requests_are_caught() {
+    let rocket = rocket::build()
+        .mount("/", routes![catch_all_route])
+        .register("/", rocket::catchers![forbidden])
+        .attach(make_cors());
+    let client = Client::tracked(rocket).unwrap();
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.bad-origin.com");
+    let response = client.get("/anything").header(origin_header).dispatch();
+    assert_eq!(response.status(), Status::Forbidden);
+    assert_eq!(response.into_string().unwrap(), "Forbidden by CORS");
+}
+
+fn handler<'r>(
+    request: &'r rocket::Request<'_>,
+    _: rocket::Data<'r>,
+) -> rocket::route::BoxFuture<'r> {
+    rocket::route::Outcome::from(request, "Not for failing requests").pin()
+}
+
+/// The responses to failing requests are replaced for every method, and no route is mounted
+#[test]
+fn failing_requests_are_failed_for_every_method() {
+    let methods = [
+        Method::Get,
+        Method::Put,
+        Method::Post,
+        Method::Delete,
+        Method::Options,
+        Method::Head,
+        Method::Trace,
+        Method::Connect,
+        Method::Patch,
+    ];
+    let routes: Vec<_> = methods
+        .iter()
+        .map(|method| rocket::Route::new(*method, "/<_..>", handler))
+        .collect();
+    let rocket = rocket::build().mount("/", routes).attach(make_cors());
+    let client = Client::tracked(rocket).unwrap();
+    assert_eq!(client.rocket().routes().count(), methods.len());
+
+    for method in methods {
+        let origin_header = Header::new(ORIGIN.as_str(), "https://www.bad-origin.com");
+        let response = client
+            .req(method, "/anything")
+            .header(origin_header)
+            .dispatch();
+        assert_eq!(response.status(), Status::Forbidden, "{}", method);
+        assert_ne!(
+            response.into_string().as_deref(),
+            Some("Not for failing requests"),
+            "{}",
+            method
+        );
+    }
+}
+
+/// A request is validated by the first fairing only, so that a later fairing cannot contradict
+/// its decision
+#[test]
+fn requests_are_validated_by_the_first_fairing_only() {
+    let allow_all = || CorsOptions::default().to_cors().expect("To not fail");
+    let origin = "https://www.bad-origin.com";
+
+    let rocket = rocket::build()
+        .mount("/", routes![cors])
+        .attach(make_cors())
+        .attach(allow_all());
+    let client = Client::tracked(rocket).unwrap();
+    let response = client
+        .get("/")
+        .header(Header::new(ORIGIN.as_str(), origin))
+        .dispatch();
+    assert_eq!(response.status(), Status::Forbidden);
+    assert!(response
+        .headers()
+        .get_one("Access-Control-Allow-Origin")
+        .is_none());
+
+    let rocket = rocket::build()
+        .mount("/", routes![cors])
+        .attach(allow_all())
+        .attach(make_cors());
+    let client = Client::tracked(rocket).unwrap();
+    let response = client
+        .get("/")
+        .header(Header::new(ORIGIN.as_str(), origin))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.headers().get_one("Access-Control-Allow-Origin"),
+        Some(origin)
+    );
+}
+
+#[get("/guarded")]
+fn guarded(cors: Guard<'_>) -> rocket_cors::Responder<&str> {
+    cors.responder("Hello guarded CORS")
+}
+
+/// Exempt routes are validated by their own guard instead of the fairing
+#[test]
+fn exempt_routes_are_not_validated_by_the_fairing() {
+    let cors = CorsOptions {
+        allowed_origins: AllowedOrigins::some_exact(&["https://www.acme.com"]),
+        ..Default::default()
+    }
+    .fairing_exempt_route("guarded")
+    .to_cors()
+    .expect("To not fail");
+    let rocket = rocket::build()
+        .mount("/", routes![cors, guarded])
+        .manage(CorsOptions::default().to_cors().expect("To not fail"))
+        .attach(cors);
+    let client = Client::tracked(rocket).unwrap();
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.example.com");
+    let response = client.get("/guarded").header(origin_header).dispatch();
+    assert!(response.status().class().is_success());
+    let origin_header = response
+        .headers()
+        .get_one("Access-Control-Allow-Origin")
+        .expect("to exist");
+    assert_eq!("https://www.example.com", origin_header);
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.example.com");
+    let response = client.get("/").header(origin_header).dispatch();
+    assert_eq!(response.status(), Status::Forbidden);
+}
+
+/// Requests outside of the fairing scope are neither validated nor decorated
+#[test]
+fn requests_outside_of_the_scope_are_untouched() {
+    let cors = CorsOptions {
+        allowed_origins: AllowedOrigins::some_exact(&["https://www.acme.com"]),
+        ..Default::default()
+    }
+    .fairing_scope(Some("/api".to_string()))
+    .to_cors()
+    .expect("To not fail");
+    let rocket = rocket::build()
+        .mount("/", routes![cors])
+        .mount("/api", routes![cors])
+        .mount("/apis", routes![cors])
+        .attach(cors);
+    let client = Client::tracked(rocket).unwrap();
+
+    for path in ["/", "/apis"] {
+        let origin_header = Header::new(ORIGIN.as_str(), "https://www.example.com");
+        let response = client.get(path).header(origin_header).dispatch();
+        assert!(response.status().class().is_success());
+
+        let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+        let response = client.get(path).header(origin_header).dispatch();
+        assert!(response
+            .headers()
+            .get_one("Access-Control-Allow-Origin")
+            .is_none());
+    }
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.example.com");
+    let response = client.get("/api").header(origin_header).dispatch();
+    assert_eq!(response.status(), Status::Forbidden);
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+    let response = client.get("/api").header(origin_header).dispatch();
+    let origin_header = response
+        .headers()
+        .get_one("Access-Control-Allow-Origin")
+        .expect("to exist");
+    assert_eq!("https://www.acme.com", origin_header);
+}
+
+#[test]
+fn cors_options_bad_request_method_with_headers_on_error() {
+    let cors = CorsOptions {
+        allowed_origins: AllowedOrigins::some_exact(&["https://www.acme.com"]),
+        allowed_methods: vec![Method::Get].into_iter().map(From::from).collect(),
+        headers_on_error: true,
+        ..Default::default()
+    }
+    .to_cors()
+    .expect("To not fail");
+    let client = Client::tracked(rocket::build().mount("/", routes![cors]).attach(cors)).unwrap();
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+    let method_header = Header::new(
+        ACCESS_CONTROL_REQUEST_METHOD.as_str(),
+        hyper::Method::POST.as_str(),
+    );
+    let req = client
+        .options("/")
+        .header(origin_header)
+        .header(method_header);
+
+    let response = req.dispatch();
+    assert_eq!(response.status(), Status::Forbidden);
+    let origin_header = response
+        .headers()
+        .get_one("Access-Control-Allow-Origin")
+        .expect("to exist");
+    assert_eq!("https://www.acme.com", origin_header);
+
+    // Origins that are not allowed still do not get any headers
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.bad-origin.com");
+    let method_header = Header::new(
+        ACCESS_CONTROL_REQUEST_METHOD.as_str(),
+        hyper::Method::GET.as_str(),
+    );
+    let req = client
+        .options("/")
+        .header(origin_header)
+        .header(method_header);
+
+    let response = req.dispatch();
+    assert_eq!(response.status(), Status::Forbidden);
+    assert!(response
+        .headers()
+        .get_one("Access-Control-Allow-Origin")
+        .is_none());
+}
+
+#[test]
+fn fairing_created_at_ignition_handles_requests() {
+    let rocket = rocket::custom(
+        rocket::Config::figment().merge(("frontend_origin", "https://www.example.com")),
+    )
+    .mount("/", routes![cors])
+    .attach(Cors::fairing_try_with(|figment| {
+        let origin: String = figment.extract_inner("frontend_origin").expect("to be set");
+        Ok(CorsOptions::default().allowed_origins(AllowedOrigins::some_exact(&[origin])))
+    }));
+    let client = Client::tracked(rocket).unwrap();
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.example.com");
+    let response = client.get("/").header(origin_header).dispatch();
+    assert!(response.status().class().is_success());
+    let origin_header = response
+        .headers()
+        .get_one("Access-Control-Allow-Origin")
+        .expect("to exist");
+    assert_eq!("https://www.example.com", origin_header);
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+    let response = client.get("/").header(origin_header).dispatch();
+    assert_eq!(response.status(), Status::Forbidden);
+}
+
+#[test]
+fn invalid_options_at_ignition_fail_ignition() {
+    let rocket = rocket::build().attach(Cors::fairing_try_with(|_| {
+        Ok(CorsOptions::default()
+            .send_wildcard(true)
+            .allow_credentials(true))
+    }));
+
+    let error = Client::tracked(rocket).expect_err("to fail");
+    assert!(matches!(
+        error.kind(),
+        rocket::error::ErrorKind::FailedFairings(_)
+    ));
+}
+
+#[derive(rocket::Responder)]
+#[response(content_type = "text")]
+struct ExposingResponse {
+    body: &'static str,
+    expose_headers: Header<'static>,
+}
+
+#[get("/expose")]
+fn exposing_route() -> ExposingResponse {
+    ExposingResponse {
+        body: "Hello CORS",
+        expose_headers: Header::new("Access-Control-Expose-Headers", "X-Route"),
+    }
+}
+
+#[test]
+fn headers_set_by_routes_are_kept_without_overwrite() {
+    let cors = CorsOptions::default()
+        .expose_headers(["X-Policy"].iter().map(|s| (*s).to_string()).collect())
+        .overwrite(false)
+        .to_cors()
+        .expect("To not fail");
+    let client = Client::tracked(
+        rocket::build()
+            .mount("/", routes![cors, exposing_route])
+            .attach(cors),
+    )
+    .unwrap();
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+    let response = client
+        .get("/expose")
+        .header(origin_header.clone())
+        .dispatch();
+    assert!(response.status().class().is_success());
+    assert_eq!(
+        response.headers().get_one("Access-Control-Expose-Headers"),
+        Some("X-Route")
+    );
+    assert_eq!(
+        response.headers().get_one("Access-Control-Allow-Origin"),
+        Some("https://www.acme.com")
+    );
+
+    let response = client.get("/").header(origin_header).dispatch();
+    assert_eq!(
+        response.headers().get_one("Access-Control-Expose-Headers"),
+        Some("X-Policy")
+    );
+}
+
+#[test]
+fn cross_origin_isolation_headers_are_added() {
+    let cors = make_cors()
+        .to_options()
+        .cross_origin_opener_policy(Some(OpenerPolicy::SameOrigin))
+        .cross_origin_embedder_policy(Some(EmbedderPolicy::RequireCorp))
+        .to_cors()
+        .expect("To not fail");
+    let client = Client::tracked(rocket::build().mount("/", routes![cors]).attach(cors)).unwrap();
+
+    // Requests that are not CORS requests
+    let response = client.get("/").dispatch();
+    assert!(response.status().class().is_success());
+    assert_eq!(
+        response.headers().get_one("Cross-Origin-Opener-Policy"),
+        Some("same-origin")
+    );
+    assert_eq!(
+        response.headers().get_one("Cross-Origin-Embedder-Policy"),
+        Some("require-corp")
+    );
+
+    // Denied requests
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.bad-origin.com");
+    let response = client.get("/").header(origin_header).dispatch();
+    assert_eq!(response.status(), Status::Forbidden);
+    assert_eq!(
+        response.headers().get_one("Cross-Origin-Opener-Policy"),
+        Some("same-origin")
+    );
+}
+
+#[derive(rocket::Responder)]
+#[response(content_type = "text")]
+struct IsolatedResponse {
+    body: &'static str,
+    opener_policy: Header<'static>,
+}
+
+#[get("/isolated")]
+fn isolated_route() -> IsolatedResponse {
+    IsolatedResponse {
+        body: "Hello CORS",
+        opener_policy: Header::new("Cross-Origin-Opener-Policy", "same-origin-allow-popups"),
+    }
+}
+
+#[test]
+fn cross_origin_isolation_headers_set_by_routes_are_kept_when_reconciling() {
+    let options = make_cors()
+        .to_options()
+        .cross_origin_opener_policy(Some(OpenerPolicy::SameOrigin))
+        .cross_origin_embedder_policy(Some(EmbedderPolicy::RequireCorp));
+    for (vary_merge, opener_policy) in [
+        (VaryMerge::Adjoin, "same-origin"),
+        (VaryMerge::Reconcile, "same-origin-allow-popups"),
+    ] {
+        let cors = options
+            .clone()
+            .vary_merge(vary_merge)
+            .to_cors()
+            .expect("To not fail");
+        let client = Client::tracked(
+            rocket::build()
+                .mount("/", routes![isolated_route])
+                .attach(cors),
+        )
+        .unwrap();
+
+        let response = client.get("/isolated").dispatch();
+        assert!(response.status().class().is_success());
+        let values: Vec<_> = response
+            .headers()
+            .get("Cross-Origin-Opener-Policy")
+            .collect();
+        assert_eq!(values, vec![opener_policy], "{:?}", vary_merge);
+        assert_eq!(
+            response.headers().get_one("Cross-Origin-Embedder-Policy"),
+            Some("require-corp")
+        );
+    }
+}
+
+#[test]
+fn cross_origin_isolation_headers_are_not_added_by_default() {
+    let client = Client::tracked(rocket()).unwrap();
+    let response = client.get("/").dispatch();
+    assert!(response
+        .headers()
+        .get_one("Cross-Origin-Opener-Policy")
+        .is_none());
+    assert!(response
+        .headers()
+        .get_one("Cross-Origin-Embedder-Policy")
+        .is_none());
+}
+
+/// Fairings with different scopes can be attached together, each with its own
+/// policy
+#[test]
+fn scoped_fairings_can_be_attached_together() {
+    let scoped = |scope: &str, origin: &str| {
+        CorsOptions {
+            allowed_origins: AllowedOrigins::some_exact(&[origin]),
+            ..Default::default()
+        }
+        .fairing_scope(Some(scope.to_string()))
+        .to_cors()
+        .expect("To not fail")
+    };
+    let rocket = rocket::build()
+        .mount("/api", routes![cors])
+        .mount("/admin", routes![cors])
+        .attach(scoped("/api", "https://www.acme.com"))
+        .attach(scoped("/admin", "https://admin.acme.com"));
+    let client = Client::tracked(rocket).unwrap();
+
+    for (path, origin, allowed) in [
+        ("/api", "https://www.acme.com", true),
+        ("/api", "https://admin.acme.com", false),
+        ("/admin", "https://admin.acme.com", true),
+        ("/admin", "https://www.acme.com", false),
+    ] {
+        let origin_header = Header::new(ORIGIN.as_str(), origin);
+        let response = client.get(path).header(origin_header).dispatch();
+        if allowed {
+            assert_eq!(response.status(), Status::Ok, "{} {}", path, origin);
+            let values: Vec<_> = response
+                .headers()
+                .get("Access-Control-Allow-Origin")
+                .collect();
+            assert_eq!(values, vec![origin], "{} {}", path, origin);
+            let vary: Vec<_> = response.headers().get("Vary").collect();
+            assert_eq!(vary, vec!["Origin"], "{} {}", path, origin);
+        } else {
+            assert_eq!(response.status(), Status::Forbidden, "{} {}", path, origin);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! This crate tests the process-wide default `Cors` used by the request guard when there is no
+//! `Cors` in managed state
+//!
+//! The default is global, so it is tested in its own crate.
+use rocket_cors as cors;
+
+use rocket::http::hyper;
+use rocket::http::{Header, Method, Status};
+use rocket::local::blocking::Client;
+use rocket::{get, routes};
+
+static ORIGIN: http::header::HeaderName = hyper::header::ORIGIN;
+static ACCESS_CONTROL_REQUEST_METHOD: http::header::HeaderName =
+    hyper::header::ACCESS_CONTROL_REQUEST_METHOD;
+
+#[get("/")]
+fn cors_responder(cors: cors::Guard<'_>) -> cors::Responder<&str> {
+    cors.responder("Hello CORS")
+}
+
+fn make_cors(origin: &str) -> cors::Cors {
+    cors::CorsOptions {
+        allowed_origins: cors::AllowedOrigins::some_exact(&[origin]),
+        allowed_methods: vec![Method::Get].into_iter().map(From::from).collect(),
+        ..Default::default()
+    }
+    .to_cors()
+    .expect("To not fail")
+}
+
+#[test]
+fn global_default_is_used_without_managed_cors() {
+    cors::Cors::set_global_default(make_cors("https://www.acme.com"));
+
+    let rocket = rocket::build()
+        .mount("/", routes![cors_responder])
+        .mount("/", cors::catch_all_options_routes());
+    let client = Client::tracked(rocket).expect("ignition not to be aborted");
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+    let response = client.get("/").header(origin_header).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.headers().get_one("Access-Control-Allow-Origin"),
+        Some("https://www.acme.com")
+    );
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+    let method_header = Header::new(
+        ACCESS_CONTROL_REQUEST_METHOD.as_str(),
+        hyper::Method::GET.as_str(),
+    );
+    let response = client
+        .options("/")
+        .header(origin_header)
+        .header(method_header)
+        .dispatch();
+    assert!(response.status().class().is_success());
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.example.com");
+    let response = client.get("/").header(origin_header).dispatch();
+    assert_eq!(response.status(), Status::Forbidden);
+
+    // Managed state takes precedence over the global default
+    let rocket = rocket::build()
+        .mount("/", routes![cors_responder])
+        .manage(make_cors("https://www.example.com"));
+    let client = Client::tracked(rocket).unwrap();
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.example.com");
+    let response = client.get("/").header(origin_header).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+}
@@ -1,10 +1,13 @@
 //! This crate tests using `rocket_cors` using the per-route handling with request guard
 use rocket_cors as cors;
 
+use rocket::futures::{future, stream};
 use rocket::http::hyper;
 use rocket::http::Method;
 use rocket::http::{Header, Status};
 use rocket::local::blocking::Client;
+use rocket::response::stream::{ByteStream, Event, EventStream, TextStream};
+use rocket::tokio::io::AsyncReadExt;
 use rocket::State;
 use rocket::{get, options, routes};
 
@@ -48,6 +51,76 @@ fn responder_unit(cors: cors::Guard<'_>) -> cors::Responder<()> {
     cors.responder(())
 }
 
+/// `Responder` wrapping a failing responder
+#[get("/responder/error")]
+fn responder_error(cors: cors::Guard<'_>) -> cors::Responder<Result<&str, Status>> {
+    cors.responder(Err(Status::NotFound))
+}
+
+/// `Responder` wrapping a failing responder, which responds to the error itself
+#[get("/responder/error/bypassed")]
+fn responder_error_bypassed(cors: cors::Guard<'_>) -> cors::Responder<Result<&str, Status>> {
+    cors.responder(Err(Status::NotFound)).bypass_catchers()
+}
+
+/// `Responder` exposing a header computed for the request
+#[get("/responder/paginated")]
+fn responder_paginated(cors: cors::Guard<'_>) -> cors::Responder<&str> {
+    cors.add_expose_headers(["X-Pagination-Total"])
+        .responder("Hello CORS")
+}
+
+/// `Responder` with a stream of server-sent events
+#[get("/stream/events")]
+fn stream_events(cors: cors::Guard<'_>) -> cors::Responder<EventStream![]> {
+    cors.responder(
+        EventStream! {
+            yield Event::data("first");
+            yield Event::data("second");
+        }
+        .heartbeat(None),
+    )
+}
+
+/// `Responder` with a stream of server-sent events that never ends
+#[get("/stream/endless")]
+fn stream_endless(cors: cors::Guard<'_>) -> cors::Responder<EventStream![]> {
+    cors.responder(
+        EventStream! {
+            yield Event::data("first");
+            future::pending::<()>().await;
+        }
+        .heartbeat(None),
+    )
+}
+
+/// `Responder` with a text stream
+#[get("/stream/text")]
+fn stream_text(cors: cors::Guard<'_>) -> cors::Responder<TextStream![&'static str]> {
+    cors.responder(TextStream::from(stream::iter(["Hello ", "CORS"])))
+}
+
+/// `Responder` with a byte stream
+#[get("/stream/bytes")]
+fn stream_bytes(cors: cors::Guard<'_>) -> cors::Responder<ByteStream![&'static [u8]]> {
+    cors.responder(ByteStream::from(stream::iter([&b"Hello "[..], b"CORS"])))
+}
+
+/// `Responder` with the decision made for the request
+#[get("/decision")]
+fn guard_decision(cors: cors::Guard<'_>) -> cors::Responder<String> {
+    let decision = match cors.decision() {
+        Some(cors::Decision::Allowed {
+            origin,
+            rule,
+            method,
+            ..
+        }) => format!("{} {:?} {}", origin, rule, method),
+        decision => format!("{:?}", decision),
+    };
+    cors.responder(decision)
+}
+
 struct SomeState;
 /// Borrow `SomeState` from Rocket
 #[get("/state")]
@@ -72,7 +145,22 @@ fn make_cors() -> cors::Cors {
 fn make_rocket() -> rocket::Rocket<rocket::Build> {
     rocket::build()
         .mount("/", routes![cors_responder, panicking_route])
-        .mount("/", routes![responder_string, responder_unit, state])
+        .mount(
+            "/",
+            routes![
+                responder_string,
+                responder_unit,
+                responder_error,
+                responder_error_bypassed,
+                responder_paginated,
+                stream_events,
+                stream_endless,
+                stream_text,
+                stream_bytes,
+                guard_decision,
+                state
+            ],
+        )
         .mount("/", cors::catch_all_options_routes()) // mount the catch all routes
         .mount("/", routes![cors_manual, cors_manual_options]) // manual OPTIOONS routes
         .manage(make_cors())
@@ -172,6 +260,133 @@ fn cors_options_catch_all_check_other_routes() {
     assert_eq!("https://www.acme.com", origin_header);
 }
 
+#[test]
+fn cors_options_catch_all_with_base() {
+    let rocket = rocket::build()
+        .mount("/", cors::catch_all_options_routes_with(100, "/api/"))
+        .manage(make_cors());
+    let client = Client::tracked(rocket).unwrap();
+
+    let preflight = |path| {
+        let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+        let method_header = Header::new(
+            ACCESS_CONTROL_REQUEST_METHOD.as_str(),
+            hyper::Method::GET.as_str(),
+        );
+        client
+            .options(path)
+            .header(origin_header)
+            .header(method_header)
+            .dispatch()
+    };
+
+    let response = preflight("/api/users");
+    assert!(response.status().class().is_success());
+    let origin_header = response
+        .headers()
+        .get_one("Access-Control-Allow-Origin")
+        .expect("to exist");
+    assert_eq!("https://www.acme.com", origin_header);
+
+    let response = preflight("/other");
+    assert_eq!(response.status(), Status::NotFound);
+}
+
+#[test]
+fn cors_options_catch_all_can_be_customized() {
+    let rocket = rocket::build()
+        .mount(
+            "/",
+            cors::CatchAllOptionsRoutes::new()
+                .status(Status::NoContent)
+                .name("preflight"),
+        )
+        .mount(
+            "/",
+            cors::CatchAllOptionsRoutes::new()
+                .base("/api")
+                .rank(100)
+                .body("Pre-flight OK"),
+        )
+        .manage(make_cors());
+    let mut names: Vec<_> = rocket
+        .routes()
+        .filter_map(|route| route.name.clone())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["cors_catch_all_options", "preflight"]);
+    let client = Client::tracked(rocket).unwrap();
+
+    let preflight = |path| {
+        let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+        let method_header = Header::new(
+            ACCESS_CONTROL_REQUEST_METHOD.as_str(),
+            hyper::Method::GET.as_str(),
+        );
+        client
+            .options(path)
+            .header(origin_header)
+            .header(method_header)
+            .dispatch()
+    };
+
+    let response = preflight("/users");
+    assert_eq!(response.status(), Status::NoContent);
+    assert_eq!(
+        response.headers().get_one("Access-Control-Allow-Origin"),
+        Some("https://www.acme.com")
+    );
+
+    let response = preflight("/api/users");
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(response.into_string().as_deref(), Some("Pre-flight OK"));
+}
+
+#[test]
+fn cors_options_routes_are_generated() {
+    let rocket = rocket::build()
+        .mount(
+            "/",
+            routes![responder_string, cors_manual, cors_manual_options],
+        )
+        .mount("/api", routes![cors_responder])
+        .attach(cors::options_routes_fairing())
+        .manage(make_cors());
+    let client = Client::tracked(rocket).unwrap();
+
+    let preflight = |path| {
+        let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+        let method_header = Header::new(
+            ACCESS_CONTROL_REQUEST_METHOD.as_str(),
+            hyper::Method::GET.as_str(),
+        );
+        client
+            .options(path)
+            .header(origin_header)
+            .header(method_header)
+            .dispatch()
+    };
+
+    for path in ["/responder/string", "/api"] {
+        let response = preflight(path);
+        assert!(response.status().class().is_success());
+        let origin_header = response
+            .headers()
+            .get_one("Access-Control-Allow-Origin")
+            .expect("to exist");
+        assert_eq!("https://www.acme.com", origin_header);
+    }
+
+    let response = preflight("/manual");
+    assert_eq!(
+        response.into_string(),
+        Some("Manual CORS Preflight".to_string())
+    );
+
+    let response = preflight("/other");
+    assert_eq!(response.status(), Status::NotFound);
+}
+
 #[test]
 fn cors_get_check() {
     let rocket = make_rocket();
@@ -329,6 +544,143 @@ fn cors_get_bad_origin() {
 /// This test ensures that on a failing CORS request, the route (along with its side effects)
 /// should never be executed.
 /// The route used will panic if executed
+#[test]
+fn responder_errors_are_caught() {
+    let client =
+        Client::tracked(make_rocket().register("/", rocket::catchers![not_found])).unwrap();
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+    let req = client.get("/responder/error").header(origin_header);
+
+    let response = req.dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    assert!(response
+        .headers()
+        .get_one("Access-Control-Allow-Origin")
+        .is_none());
+    assert_eq!(response.into_string().as_deref(), Some("Not found"));
+}
+
+#[rocket::catch(404)]
+fn not_found() -> &'static str {
+    "Not found"
+}
+
+#[test]
+fn responder_errors_bypassing_catchers_have_cors_headers() {
+    let client =
+        Client::tracked(make_rocket().register("/", rocket::catchers![not_found])).unwrap();
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+    let req = client
+        .get("/responder/error/bypassed")
+        .header(origin_header);
+
+    let response = req.dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    let origin_header = response
+        .headers()
+        .get_one("Access-Control-Allow-Origin")
+        .expect("to exist");
+    assert_eq!("https://www.acme.com", origin_header);
+    assert_ne!(response.into_string().as_deref(), Some("Not found"));
+}
+
+#[test]
+fn responders_can_expose_additional_headers() {
+    let client = Client::tracked(make_rocket()).unwrap();
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+    let req = client.get("/responder/paginated").header(origin_header);
+
+    let response = req.dispatch();
+    assert!(response.status().class().is_success());
+    let exposed = response
+        .headers()
+        .get_one("Access-Control-Expose-Headers")
+        .expect("to exist");
+    assert_eq!("X-Pagination-Total", exposed);
+
+    // Other routes only expose the configured headers
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+    let response = client.get("/").header(origin_header).dispatch();
+    assert!(response
+        .headers()
+        .get_one("Access-Control-Expose-Headers")
+        .is_none());
+}
+
+#[test]
+fn responders_can_wrap_streams() {
+    let client = Client::tracked(make_rocket()).unwrap();
+
+    for (path, content_type, body) in [
+        (
+            "/stream/events",
+            "text/event-stream",
+            "data:first\n\ndata:second\n\n",
+        ),
+        ("/stream/text", "text/plain; charset=utf-8", "Hello CORS"),
+        ("/stream/bytes", "application/octet-stream", "Hello CORS"),
+    ] {
+        let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+        let response = client.get(path).header(origin_header).dispatch();
+        assert_eq!(response.status(), Status::Ok, "{}", path);
+        assert_eq!(
+            response.headers().get_one("Access-Control-Allow-Origin"),
+            Some("https://www.acme.com"),
+            "{}",
+            path
+        );
+        assert_eq!(
+            response.headers().get_one("Content-Type"),
+            Some(content_type),
+            "{}",
+            path
+        );
+        assert_eq!(response.into_string().as_deref(), Some(body), "{}", path);
+    }
+}
+
+#[rocket::async_test]
+async fn stream_responders_have_cors_headers_before_the_body() {
+    let client = rocket::local::asynchronous::Client::tracked(make_rocket())
+        .await
+        .unwrap();
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+    let mut response = client
+        .get("/stream/endless")
+        .header(origin_header)
+        .dispatch()
+        .await;
+    assert_eq!(
+        response.headers().get_one("Access-Control-Allow-Origin"),
+        Some("https://www.acme.com")
+    );
+
+    // The stream never ends, so only read the first event
+    let mut body = Vec::new();
+    while !body.ends_with(b"\n\n") {
+        let mut chunk = [0; 64];
+        let read = response.read(&mut chunk).await.expect("to read the body");
+        assert_ne!(read, 0, "the stream should not end");
+        body.extend_from_slice(&chunk[..read]);
+    }
+    assert_eq!(body, b"data:first\n\n");
+}
+
+#[test]
+fn guard_exposes_the_decision() {
+    let client = Client::tracked(make_rocket()).unwrap();
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+    let response = client.get("/decision").header(origin_header).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert_eq!(
+        response.into_string().as_deref(),
+        Some("https://www.acme.com Some(Exact) GET")
+    );
+
+    let response = client.get("/decision").dispatch();
+    assert_eq!(response.into_string().as_deref(), Some("None"));
+}
+
 #[test]
 fn routes_failing_checks_are_not_executed() {
     let rocket = make_rocket();
@@ -375,3 +727,51 @@ fn overridden_options_routes_are_used() {
     let body_str = response.into_string();
     assert_eq!(body_str, Some("Manual CORS Preflight".to_string()));
 }
+
+#[test]
+fn cors_options_catch_all_bad_request_method_with_headers_on_error() {
+    let cors = cors::CorsOptions {
+        allowed_origins: cors::AllowedOrigins::some_exact(&["https://www.acme.com"]),
+        allowed_methods: vec![Method::Get].into_iter().map(From::from).collect(),
+        headers_on_error: true,
+        ..Default::default()
+    }
+    .to_cors()
+    .expect("To not fail");
+    let rocket = rocket::build()
+        .mount("/", routes![cors_responder])
+        .mount("/", cors::catch_all_options_routes())
+        .manage(cors);
+    let client = Client::tracked(rocket).unwrap();
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+    let method_header = Header::new(
+        ACCESS_CONTROL_REQUEST_METHOD.as_str(),
+        hyper::Method::POST.as_str(),
+    );
+    let req = client
+        .options("/")
+        .header(origin_header)
+        .header(method_header);
+
+    let response = req.dispatch();
+    assert_eq!(response.status(), Status::Forbidden);
+    let origin_header = response
+        .headers()
+        .get_one("Access-Control-Allow-Origin")
+        .expect("to exist");
+    assert_eq!("https://www.acme.com", origin_header);
+}
+
+/// The `Guard` cannot validate requests without a `Cors` in managed state, so Rocket should not
+/// launch
+#[test]
+fn guard_without_managed_cors_aborts_ignition() {
+    let rocket = rocket::build().mount("/", routes![cors_responder]);
+
+    let error = Client::tracked(rocket).expect_err("to fail");
+    assert!(matches!(
+        error.kind(),
+        rocket::error::ErrorKind::SentinelAborts(_)
+    ));
+}
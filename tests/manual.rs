@@ -5,7 +5,7 @@ use rocket::http::{Header, Status};
 use rocket::local::blocking::Client;
 use rocket::response::Responder;
 use rocket::State;
-use rocket::{get, options, routes};
+use rocket::{catch, catchers, get, options, routes, Request};
 use rocket_cors::*;
 
 static ORIGIN: http::header::HeaderName = hyper::header::ORIGIN;
@@ -24,7 +24,7 @@ fn cors(options: &State<Cors>) -> impl Responder<'_, '_> {
 
 #[get("/panic")]
 fn panicking_route(options: &State<Cors>) -> impl Responder<'_, '_> {
-    options.inner().respond_borrowed(|_| {
+    options.inner().respond_borrowed(|_| -> () {
         panic!("This route will panic");
     })
 }
@@ -71,6 +71,17 @@ fn borrow<'r, 'o: 'r>(
     })
 }
 
+/// A catcher adding the CORS headers to its response
+#[catch(404)]
+async fn not_found(request: &Request<'_>) -> Result<rocket_cors::Responder<Status>, Error> {
+    let options = request
+        .rocket()
+        .state::<Cors>()
+        .expect("Cors to be managed");
+    let headers = options.validate_request(request).await?;
+    Ok(headers.responder(Status::NotFound).bypass_catchers())
+}
+
 fn make_cors_options() -> CorsOptions {
     let allowed_origins = AllowedOrigins::some_exact(&["https://www.acme.com"]);
 
@@ -100,6 +111,7 @@ fn rocket() -> rocket::Rocket<rocket::Build> {
         .mount("/", routes![cors, panicking_route])
         .mount("/", routes![owned, owned_options])
         .mount("/", catch_all_options_routes()) // mount the catch all routes
+        .register("/", catchers![not_found])
         .manage(make_cors_options().to_cors().expect("Not to fail"))
 }
 
@@ -383,3 +395,25 @@ fn cors_get_owned_check() {
     let body_str = response.into_string();
     assert_eq!(body_str, Some("Hello CORS Owned".to_string()));
 }
+
+#[test]
+fn catchers_can_validate_requests() {
+    let client = Client::tracked(rocket()).unwrap();
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.acme.com");
+    let response = client.get("/missing").header(origin_header).dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+    let origin_header = response
+        .headers()
+        .get_one("Access-Control-Allow-Origin")
+        .expect("to exist");
+    assert_eq!("https://www.acme.com", origin_header);
+
+    let origin_header = Header::new(ORIGIN.as_str(), "https://www.bad-origin.com");
+    let response = client.get("/missing").header(origin_header).dispatch();
+    assert!(response.status().class().is_server_error());
+    assert!(response
+        .headers()
+        .get_one("Access-Control-Allow-Origin")
+        .is_none());
+}
@@ -2,13 +2,15 @@
 //!
 //! In this example, you typically have an application wide `Cors` struct except for one specific
 //! `ping` route that you want to allow all Origins to access.
+use std::sync::Arc;
+
 use rocket::http::hyper;
 use rocket::http::{Header, Method, Status};
 use rocket::local::blocking::Client;
 use rocket::response::Responder;
-use rocket::{get, options, routes};
+use rocket::{get, options, routes, State};
 
-use rocket_cors::{AllowedHeaders, AllowedOrigins, CorsOptions, Guard};
+use rocket_cors::{AllowedHeaders, AllowedOrigins, Cors, CorsOptions, Guard};
 
 static ORIGIN: http::header::HeaderName = hyper::header::ORIGIN;
 static ACCESS_CONTROL_REQUEST_METHOD: http::header::HeaderName =
@@ -22,20 +24,21 @@ fn app(cors: Guard<'_>) -> rocket_cors::Responder<&str> {
     cors.responder("Hello CORS!")
 }
 
+/// The `Cors` struct for the "ping" route, shared with an `Arc` so that it is only created once
+struct PingCors(Arc<Cors>);
+
 /// The special "ping" route
 #[get("/ping")]
-fn ping<'r, 'o: 'r>() -> impl Responder<'r, 'o> {
-    let cors = cors_options_all().to_cors()?;
-    cors.respond_owned(|guard| guard.responder("Pong!"))
+fn ping(cors: &State<PingCors>) -> impl Responder<'_, '_> {
+    Arc::clone(&cors.0).respond_shared(|guard| guard.responder("Pong!"))
 }
 
 /// You need to define an OPTIONS route for preflight checks if you want to use `Cors` struct
 /// that is not in Rocket's managed state.
 /// These routes can just return the unit type `()`
 #[options("/ping")]
-fn ping_options<'r, 'o: 'r>() -> impl Responder<'r, 'o> {
-    let cors = cors_options_all().to_cors()?;
-    cors.respond_owned(|guard| guard.responder(()))
+fn ping_options(cors: &State<PingCors>) -> impl Responder<'_, '_> {
+    Arc::clone(&cors.0).respond_shared(|guard| guard.responder(()))
 }
 
 /// Returns the "application wide" Cors struct
@@ -53,9 +56,6 @@ fn cors_options() -> CorsOptions {
 }
 
 /// A special struct that allows all origins
-///
-/// Note: In your real application, you might want to use something like `lazy_static` to generate
-/// a `&'static` reference to this instead of creating a new struct on every request.
 fn cors_options_all() -> CorsOptions {
     // You can also deserialize this
     Default::default()
@@ -66,6 +66,9 @@ fn rocket() -> rocket::Rocket<rocket::Build> {
         .mount("/", routes![app, ping, ping_options,])
         .mount("/", rocket_cors::catch_all_options_routes()) // mount the catch all routes
         .manage(cors_options().to_cors().expect("Not to fail"))
+        .manage(PingCors(Arc::new(
+            cors_options_all().to_cors().expect("Not to fail"),
+        )))
 }
 
 #[test]